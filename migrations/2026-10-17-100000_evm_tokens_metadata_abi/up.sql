@@ -0,0 +1,2 @@
+ALTER TABLE evm_tokens
+ADD COLUMN metadata_abi VARCHAR(16) NOT NULL DEFAULT 'string' CHECK (metadata_abi IN ('string', 'bytes32'));
@@ -0,0 +1,18 @@
+CREATE TABLE evm_tokens_old (
+    id VARCHAR(255) NOT NULL PRIMARY KEY,
+    chain_id BIGINT NOT NULL CHECK (chain_id > 0),
+    address VARCHAR(255) NOT NULL CHECK (LENGTH(address) = 42),
+    symbol VARCHAR(255) NOT NULL,
+    decimals INT NOT NULL CHECK (decimals BETWEEN 0 AND 255),
+    name VARCHAR(255) NOT NULL,
+    metadata_abi VARCHAR(16) NOT NULL DEFAULT 'string' CHECK (metadata_abi IN ('string', 'bytes32'))
+);
+
+INSERT INTO evm_tokens_old (id, chain_id, address, symbol, decimals, name, metadata_abi)
+SELECT id, chain_id, address, symbol, decimals, name, metadata_abi
+FROM evm_tokens
+WHERE symbol IS NOT NULL AND name IS NOT NULL;
+
+DROP TABLE evm_tokens;
+
+ALTER TABLE evm_tokens_old RENAME TO evm_tokens;
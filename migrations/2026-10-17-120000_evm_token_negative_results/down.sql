@@ -0,0 +1 @@
+DROP TABLE IF EXISTS evm_token_negative_results;
@@ -0,0 +1,9 @@
+CREATE TABLE evm_token_negative_results (
+    id VARCHAR(255) NOT NULL PRIMARY KEY,
+    chain_id BIGINT NOT NULL CHECK (chain_id > 0),
+    address VARCHAR(255) NOT NULL CHECK (LENGTH(address) = 42),
+    reason VARCHAR(32) NOT NULL CHECK (reason IN ('not_a_contract', 'invalid_metadata')),
+    message TEXT NOT NULL,
+    -- Unix seconds
+    recorded_at BIGINT NOT NULL
+);
@@ -0,0 +1,4 @@
+DROP INDEX IF EXISTS evm_tokens_last_verified_at;
+ALTER TABLE evm_tokens DROP COLUMN source_block;
+ALTER TABLE evm_tokens DROP COLUMN last_verified_at;
+ALTER TABLE evm_tokens DROP COLUMN fetched_at;
@@ -0,0 +1,7 @@
+-- Unix seconds. Rows cached before this migration count as never verified, so the background
+-- refresh picks them up first.
+ALTER TABLE evm_tokens ADD COLUMN fetched_at BIGINT NOT NULL DEFAULT 0;
+ALTER TABLE evm_tokens ADD COLUMN last_verified_at BIGINT NOT NULL DEFAULT 0;
+ALTER TABLE evm_tokens ADD COLUMN source_block BIGINT;
+
+CREATE INDEX evm_tokens_last_verified_at ON evm_tokens (last_verified_at);
@@ -0,0 +1,3 @@
+-- Unix seconds of the last refresh attempt since the token was verified. A failing token waits
+-- out the retry delay instead of heading the refresh queue on every run.
+ALTER TABLE evm_tokens ADD COLUMN refresh_attempted_at BIGINT;
@@ -0,0 +1,2 @@
+DROP TABLE IF EXISTS evm_token_negative_results;
+DROP TABLE IF EXISTS evm_tokens;
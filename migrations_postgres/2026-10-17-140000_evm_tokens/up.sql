@@ -0,0 +1,23 @@
+CREATE TABLE evm_tokens (
+    id VARCHAR(255) NOT NULL PRIMARY KEY,
+    chain_id BIGINT NOT NULL CHECK (chain_id > 0),
+    address VARCHAR(255) NOT NULL CHECK (LENGTH(address) = 42),
+    symbol VARCHAR(255),
+    decimals INT NOT NULL CHECK (decimals BETWEEN 0 AND 255),
+    name VARCHAR(255),
+    metadata_abi VARCHAR(16) NOT NULL DEFAULT 'string' CHECK (metadata_abi IN ('string', 'bytes32')),
+    fetched_at TIMESTAMPTZ NOT NULL DEFAULT now(),
+    last_verified_at TIMESTAMPTZ NOT NULL DEFAULT now(),
+    source_block BIGINT CHECK (source_block >= 0)
+);
+
+CREATE INDEX evm_tokens_last_verified_at ON evm_tokens (last_verified_at);
+
+CREATE TABLE evm_token_negative_results (
+    id VARCHAR(255) NOT NULL PRIMARY KEY,
+    chain_id BIGINT NOT NULL CHECK (chain_id > 0),
+    address VARCHAR(255) NOT NULL CHECK (LENGTH(address) = 42),
+    reason VARCHAR(32) NOT NULL CHECK (reason IN ('not_a_contract', 'invalid_metadata')),
+    message TEXT NOT NULL,
+    recorded_at TIMESTAMPTZ NOT NULL
+);
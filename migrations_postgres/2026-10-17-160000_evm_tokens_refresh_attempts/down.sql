@@ -0,0 +1 @@
+ALTER TABLE evm_tokens DROP COLUMN refresh_attempted_at;
@@ -1,21 +1,186 @@
-use serde::Deserialize;
+use std::{
+    collections::{HashMap, HashSet},
+    fmt,
+    path::PathBuf,
+    str::FromStr,
+};
+
+use log::warn;
+use serde::{Deserialize, Deserializer, Serialize, de::DeserializeOwned};
+use thiserror::Error;
 
 use crate::types::ChainId;
 
 pub(crate) const CHAINLIST_API_URL: &str = "https://chainlist.org/rpcs.json";
 
-#[derive(Deserialize, Debug, Clone)]
+/// Major chains with public RPCs, in Chainlist's format, for starting without network access.
+/// Refresh with `curl https://chainlist.org/rpcs.json` and trim to the chains worth bundling.
+const BUNDLED_SNAPSHOT: &str = include_str!("chainlist_snapshot.json");
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
 pub struct Chain {
     pub name: String,
     pub chain: String,
     #[serde(rename = "chainId")]
     pub chain_id: ChainId,
+    #[serde(rename = "shortName", default, skip_serializing_if = "Option::is_none")]
+    pub short_name: Option<String>,
+    /// SLIP-44 coin type of the native currency, used in its CAIP-19 asset id.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub slip44: Option<u32>,
+    /// `None` when missing or malformed.
+    #[serde(
+        rename = "nativeCurrency",
+        default,
+        deserialize_with = "lenient_native_currency",
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub native_currency: Option<NativeCurrency>,
+    /// Malformed entries are left out.
+    #[serde(
+        default,
+        deserialize_with = "lenient_explorers",
+        skip_serializing_if = "Vec::is_empty"
+    )]
+    pub explorers: Vec<Explorer>,
     pub rpc: Vec<Rpc>,
 }
 
-#[derive(Deserialize, Debug, Clone)]
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+pub struct NativeCurrency {
+    pub name: String,
+    pub symbol: String,
+    pub decimals: u8,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+pub struct Explorer {
+    pub name: String,
+    pub url: String,
+    /// E.g. `EIP3091` for explorers with `/tx/<hash>` and `/address/<address>` pages.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub standard: Option<String>,
+}
+
+fn lenient_native_currency<'de, D: Deserializer<'de>>(
+    deserializer: D,
+) -> Result<Option<NativeCurrency>, D::Error> {
+    let value = Option::<serde_json::Value>::deserialize(deserializer)?;
+    Ok(value.and_then(|value| lenient("nativeCurrency", value)))
+}
+
+fn lenient_explorers<'de, D: Deserializer<'de>>(
+    deserializer: D,
+) -> Result<Vec<Explorer>, D::Error> {
+    let explorers = match Option::<serde_json::Value>::deserialize(deserializer)? {
+        None => Vec::new(),
+        Some(serde_json::Value::Array(explorers)) => explorers,
+        Some(other) => {
+            warn!("Ignoring malformed Chainlist explorers: {other}");
+            Vec::new()
+        }
+    };
+    Ok(explorers
+        .into_iter()
+        .filter_map(|explorer| lenient("explorer", explorer))
+        .collect())
+}
+
+/// `value` as a `T`, or `None` with a warning. Chain metadata is informational, so a malformed
+/// value is dropped rather than failing the whole list, and with it every chain's RPCs.
+fn lenient<T: DeserializeOwned>(field: &str, value: serde_json::Value) -> Option<T> {
+    match serde_json::from_value(value.clone()) {
+        Ok(parsed) => Some(parsed),
+        Err(e) => {
+            warn!("Ignoring malformed Chainlist {field} {value}: {e}");
+            None
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
 pub struct Rpc {
     pub url: String,
+    /// What the operator says it records about requests; `None` when Chainlist does not say.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub tracking: Option<Tracking>,
+    #[serde(
+        rename = "isOpenSource",
+        default,
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub is_open_source: Option<bool>,
+}
+
+/// Chainlist's `tracking` levels, from most to least private.
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+#[serde(rename_all = "lowercase")]
+pub enum Tracking {
+    None,
+    Limited,
+    Yes,
+    /// A level this crate does not know yet; treated as tracking.
+    #[serde(other)]
+    Unknown,
+}
+
+/// Where a list of chains in Chainlist's `rpcs.json` format comes from.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ChainlistSource {
+    Url(String),
+    File(PathBuf),
+}
+
+impl FromStr for ChainlistSource {
+    type Err = String;
+
+    /// `http(s)://` URLs are fetched; `file://` URLs and anything without a scheme are local
+    /// paths.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        if let Some(path) = s.strip_prefix("file://") {
+            return Ok(Self::File(PathBuf::from(path)));
+        }
+        match url::Url::parse(s) {
+            Ok(url) if matches!(url.scheme(), "http" | "https") => Ok(Self::Url(s.to_string())),
+            // Windows drive letters parse as one-letter schemes.
+            Ok(url) if url.scheme().len() > 1 => Err(format!(
+                "unsupported scheme {}; expected http(s), file or a path",
+                url.scheme()
+            )),
+            _ => Ok(Self::File(PathBuf::from(s))),
+        }
+    }
+}
+
+impl fmt::Display for ChainlistSource {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Url(url) => f.write_str(url),
+            Self::File(path) => write!(f, "{}", path.display()),
+        }
+    }
+}
+
+#[derive(Debug, Error)]
+pub enum ChainlistError {
+    #[error(transparent)]
+    Http(#[from] reqwest::Error),
+    #[error("failed to read chain list {path}: {source}")]
+    Read {
+        path: PathBuf,
+        source: std::io::Error,
+    },
+    #[error("invalid chain list {path}: {source}")]
+    Parse {
+        path: PathBuf,
+        source: serde_json::Error,
+    },
+}
+
+impl ChainlistError {
+    pub fn is_timeout(&self) -> bool {
+        matches!(self, Self::Http(e) if e.is_timeout())
+    }
 }
 
 pub async fn fetch_chains(
@@ -27,21 +192,279 @@ pub async fn fetch_chains(
     Ok(body)
 }
 
+/// Chains from `source`: fetched with `client`, or read from disk.
+pub async fn load_chains(
+    client: &reqwest::Client,
+    source: &ChainlistSource,
+) -> Result<Vec<Chain>, ChainlistError> {
+    match source {
+        ChainlistSource::Url(url) => Ok(fetch_chains(client, url).await?),
+        ChainlistSource::File(path) => {
+            let read_path = path.clone();
+            let contents = tokio::task::spawn_blocking(move || std::fs::read(read_path))
+                .await
+                .map_err(std::io::Error::other)
+                .and_then(|read| read)
+                .map_err(|source| ChainlistError::Read {
+                    path: path.clone(),
+                    source,
+                })?;
+            serde_json::from_slice(&contents).map_err(|source| ChainlistError::Parse {
+                path: path.clone(),
+                source,
+            })
+        }
+    }
+}
+
+/// One list from `lists` given in priority order. A chain listed more than once keeps the first
+/// list's name and details; its RPCs are all of the lists' RPCs in order, without repeated URLs.
+pub fn merge_chains(lists: Vec<Vec<Chain>>) -> Vec<Chain> {
+    let mut merged: Vec<Chain> = Vec::new();
+    let mut index: HashMap<ChainId, usize> = HashMap::new();
+    let mut urls: Vec<HashSet<String>> = Vec::new();
+
+    for chain in lists.into_iter().flatten() {
+        let i = *index.entry(chain.chain_id).or_insert_with(|| {
+            merged.push(Chain {
+                rpc: Vec::new(),
+                ..chain.clone()
+            });
+            urls.push(HashSet::new());
+            merged.len() - 1
+        });
+        for rpc in chain.rpc {
+            if urls[i].insert(rpc.url.trim().to_string()) {
+                merged[i].rpc.push(rpc);
+            }
+        }
+    }
+    merged
+}
+
+/// The chains bundled with the binary.
+pub fn bundled_chains() -> Vec<Chain> {
+    serde_json::from_str(BUNDLED_SNAPSHOT).expect("bundled Chainlist snapshot is valid")
+}
+
 #[cfg(test)]
 mod tests {
+    use serde_json::json;
+
     use super::*;
 
+    #[test]
+    fn chain_deserializes_native_currency_and_explorers() {
+        let chain: Chain = serde_json::from_value(json!({
+            "name": "Ethereum Mainnet",
+            "chain": "ETH",
+            "chainId": 1,
+            "shortName": "eth",
+            "nativeCurrency": { "name": "Ether", "symbol": "ETH", "decimals": 18 },
+            "explorers": [
+                { "name": "etherscan", "url": "https://etherscan.io", "standard": "EIP3091" },
+                { "name": "blockscout", "url": "https://eth.blockscout.com" }
+            ],
+            "rpc": [],
+        }))
+        .unwrap();
+
+        assert_eq!(chain.short_name.as_deref(), Some("eth"));
+        assert_eq!(
+            chain.native_currency,
+            Some(NativeCurrency {
+                name: "Ether".to_string(),
+                symbol: "ETH".to_string(),
+                decimals: 18,
+            })
+        );
+        assert_eq!(chain.explorers.len(), 2);
+        assert_eq!(chain.explorers[0].standard.as_deref(), Some("EIP3091"));
+        assert_eq!(chain.explorers[1].standard, None);
+
+        let minimal: Chain = serde_json::from_value(
+            json!({ "name": "Bare", "chain": "BARE", "chainId": 7, "rpc": [] }),
+        )
+        .unwrap();
+        assert!(minimal.native_currency.is_none());
+        assert!(minimal.explorers.is_empty());
+    }
+
+    #[test]
+    fn malformed_native_currency_and_explorers_do_not_fail_the_list() {
+        let chains: Vec<Chain> = serde_json::from_value(json!([
+            {
+                "name": "Broken",
+                "chain": "BRK",
+                "chainId": 7,
+                "nativeCurrency": { "name": "Broken", "symbol": "BRK", "decimals": "eighteen" },
+                "explorers": [
+                    { "name": "no url" },
+                    { "name": "brkscan", "url": "https://brkscan.example" },
+                ],
+                "rpc": [{ "url": "https://rpc.brk.example" }],
+            },
+            {
+                "name": "Ethereum Mainnet",
+                "chain": "ETH",
+                "chainId": 1,
+                "nativeCurrency": { "name": "Ether", "symbol": "ETH", "decimals": 18 },
+                "explorers": "https://etherscan.io",
+                "rpc": [{ "url": "https://eth.example" }],
+            },
+        ]))
+        .unwrap();
+
+        assert_eq!(chains.len(), 2);
+        assert!(chains[0].native_currency.is_none());
+        assert_eq!(chains[0].explorers.len(), 1);
+        assert_eq!(chains[0].explorers[0].name, "brkscan");
+        assert_eq!(chains[0].rpc.len(), 1);
+        assert_eq!(
+            chains[1]
+                .native_currency
+                .as_ref()
+                .map(|c| c.symbol.as_str()),
+            Some("ETH")
+        );
+        assert!(chains[1].explorers.is_empty());
+    }
+
+    #[test]
+    fn rpc_deserializes_tracking_and_open_source() {
+        let rpcs: Vec<Rpc> = serde_json::from_value(json!([
+            { "url": "https://a.test", "tracking": "none", "isOpenSource": true },
+            { "url": "https://b.test", "tracking": "limited" },
+            { "url": "https://c.test", "tracking": "sometimes" },
+            { "url": "https://d.test" },
+        ]))
+        .unwrap();
+
+        assert_eq!(rpcs[0].tracking, Some(Tracking::None));
+        assert_eq!(rpcs[0].is_open_source, Some(true));
+        assert_eq!(rpcs[1].tracking, Some(Tracking::Limited));
+        assert_eq!(rpcs[1].is_open_source, None);
+        assert_eq!(rpcs[2].tracking, Some(Tracking::Unknown));
+        assert_eq!(rpcs[3].tracking, None);
+    }
+
+    #[test]
+    fn bundled_snapshot_lists_mainnet_with_rpcs() {
+        let chains = bundled_chains();
+        let mainnet = chains.iter().find(|c| c.chain_id == 1).expect("mainnet");
+        assert_eq!(mainnet.name, "Ethereum Mainnet");
+        assert!(chains.iter().all(|c| !c.rpc.is_empty()));
+    }
+
+    fn chain(chain_id: ChainId, name: &str, urls: &[&str]) -> Chain {
+        Chain {
+            name: name.to_string(),
+            chain: "TST".to_string(),
+            chain_id,
+            short_name: None,
+            slip44: None,
+            native_currency: None,
+            explorers: Vec::new(),
+            rpc: urls
+                .iter()
+                .map(|url| Rpc {
+                    url: url.to_string(),
+                    tracking: None,
+                    is_open_source: None,
+                })
+                .collect(),
+        }
+    }
+
+    #[test]
+    fn merge_keeps_priority_order_and_drops_repeated_urls() {
+        let curated = vec![chain(
+            1,
+            "Curated Mainnet",
+            &["https://private.test", "https://a.test"],
+        )];
+        let public = vec![
+            chain(
+                1,
+                "Ethereum Mainnet",
+                &["https://a.test ", "https://b.test"],
+            ),
+            chain(10, "OP Mainnet", &["https://op.test"]),
+        ];
+
+        let merged = merge_chains(vec![curated, public]);
+        assert_eq!(merged.len(), 2);
+        assert_eq!(merged[0].name, "Curated Mainnet");
+        let urls: Vec<&str> = merged[0].rpc.iter().map(|rpc| rpc.url.as_str()).collect();
+        assert_eq!(
+            urls,
+            ["https://private.test", "https://a.test", "https://b.test"]
+        );
+        assert_eq!(merged[1].chain_id, 10);
+    }
+
+    #[test]
+    fn sources_are_urls_or_paths() {
+        let source = |s: &str| s.parse::<ChainlistSource>();
+        assert_eq!(
+            source("https://chainlist.org/rpcs.json"),
+            Ok(ChainlistSource::Url(
+                "https://chainlist.org/rpcs.json".to_string()
+            ))
+        );
+        assert_eq!(
+            source("file:///etc/token-api/rpcs.json"),
+            Ok(ChainlistSource::File(PathBuf::from(
+                "/etc/token-api/rpcs.json"
+            )))
+        );
+        assert_eq!(
+            source("config/rpcs.json"),
+            Ok(ChainlistSource::File(PathBuf::from("config/rpcs.json")))
+        );
+        assert!(source("ftp://example.com/rpcs.json").is_err());
+    }
+
     #[tokio::test]
+    async fn load_chains_reads_a_local_file() {
+        let path = std::env::temp_dir().join(format!(
+            "token_api_chainlist_source_{}.json",
+            std::process::id()
+        ));
+        std::fs::write(
+            &path,
+            json!([{ "name": "Local", "chain": "LOC", "chainId": 7, "rpc": [] }]).to_string(),
+        )
+        .unwrap();
+
+        let client = reqwest::Client::new();
+        let chains = load_chains(&client, &ChainlistSource::File(path.clone()))
+            .await
+            .unwrap();
+        assert_eq!(chains[0].name, "Local");
+
+        std::fs::write(&path, "not json").unwrap();
+        let err = load_chains(&client, &ChainlistSource::File(path.clone()))
+            .await
+            .unwrap_err();
+        assert!(matches!(err, ChainlistError::Parse { .. }), "{err}");
+
+        std::fs::remove_file(&path).unwrap();
+        let err = load_chains(&client, &ChainlistSource::File(path))
+            .await
+            .unwrap_err();
+        assert!(matches!(err, ChainlistError::Read { .. }), "{err}");
+    }
+
+    #[tokio::test]
+    #[ignore = "live chainlist.org"]
     async fn test_get_chainlist_response() {
         let client = reqwest::Client::new();
         let response = fetch_chains(&client, CHAINLIST_API_URL).await;
 
         match response {
             Ok(response) => println!("{:?}", response),
-            Err(e) => {
-                eprintln!("Error: {:?}", e);
-                assert!(false);
-            }
+            Err(e) => panic!("Error: {:?}", e),
         }
     }
 }
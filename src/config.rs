@@ -0,0 +1,784 @@
+//! Server configuration: an optional TOML file (path in `CONFIG_FILE`) overlaid with environment
+//! variables. File keys are the lowercase env var names, e.g. `port = 8080` or
+//! `chainlist_ttl_secs = 86400`; `CORS_ORIGINS` and `CHAINLIST_SOURCES` are comma separated,
+//! `cors_origins` and `chainlist_sources` lists.
+//! Per-chain RPC overrides exist only in the file, as `[rpc_overrides.<chain id>]` tables.
+//! Secrets for `${NAME}` placeholders in Chainlist URLs are an `[rpc_secrets]` table or
+//! `RPC_SECRETS=NAME=value,...`.
+
+use std::{
+    collections::{BTreeMap, HashMap},
+    env, fs,
+    num::NonZeroUsize,
+    path::PathBuf,
+    str::FromStr,
+    time::Duration,
+};
+
+use serde::Deserialize;
+use thiserror::Error;
+
+use crate::{
+    chainlist::{CHAINLIST_API_URL, ChainlistSource},
+    repositories::{RepositoryBackend, redis::evm_token::DEFAULT_KEY_PREFIX},
+    services::{
+        chainlist::{RpcPolicy, RpcSecrets, TrackingPolicy},
+        provider::RpcOverride,
+    },
+    types::ChainId,
+};
+
+const CHAINLIST_TTL: Duration = Duration::from_hours(24);
+/// How long to reuse the same Fallback `RpcClient` (keeps Alloy transport rankings; refresh picks
+/// up new Chainlist URLs).
+const PROVIDER_CACHE_TTL: Duration = Duration::from_secs(15 * 60);
+/// Parallel transport fan-out for the Alloy `FallbackLayer`, which ranks latency and stability.
+const FALLBACK_ACTIVE_CAP: usize = 32;
+/// How long an address that is not an ERC-20 token is answered from storage before the chain is
+/// asked again.
+const NEGATIVE_CACHE_TTL: Duration = Duration::from_hours(1);
+/// How often stored tokens are re-verified against the chain, and how old a verification may get
+/// before that.
+const TOKEN_REFRESH_INTERVAL: Duration = Duration::from_hours(1);
+const TOKEN_MAX_AGE: Duration = Duration::from_hours(7 * 24);
+/// Most tokens re-verified per refresh run.
+const TOKEN_REFRESH_BATCH_SIZE: usize = 500;
+/// How long a token that failed to refresh waits before the next attempt.
+const TOKEN_REFRESH_RETRY: Duration = Duration::from_hours(6);
+/// Tokens kept in the in-process cache in front of the repository; `0` disables it.
+const TOKEN_CACHE_CAPACITY: usize = 10_000;
+/// How long tokens stay in the shared Redis cache.
+const REDIS_CACHE_TTL: Duration = Duration::from_hours(24);
+
+#[derive(Debug, Clone)]
+pub struct Config {
+    pub host: String,
+    pub port: u16,
+    pub workers: usize,
+    /// Storage location; its scheme selects [`Config::repository_backend`].
+    pub database_url: String,
+    /// Chain lists merged in priority order; `chainlist_url` alone unless `chainlist_sources` is
+    /// set.
+    pub chainlist_sources: Vec<ChainlistSource>,
+    pub chainlist_ttl: Duration,
+    /// Where the last good Chainlist response is kept for starting while Chainlist is down.
+    pub chainlist_snapshot_path: Option<PathBuf>,
+    /// Whether the chains bundled with the binary stand in when Chainlist and the snapshot are not
+    /// available.
+    pub chainlist_bundled_snapshot: bool,
+    pub provider_cache_ttl: Duration,
+    pub fallback_active_cap: NonZeroUsize,
+    pub negative_cache_ttl: Duration,
+    pub token_refresh_interval: Duration,
+    pub token_max_age: Duration,
+    pub token_refresh_batch_size: usize,
+    pub token_refresh_retry: Duration,
+    /// `None` disables the in-process token cache.
+    pub token_cache_capacity: Option<NonZeroUsize>,
+    pub redis: Option<RedisConfig>,
+    pub cors: Cors,
+    /// Configured RPC endpoints per chain; their `Debug` output hides URL paths and header values.
+    pub rpc_overrides: HashMap<ChainId, RpcOverride>,
+    /// Values for placeholders in Chainlist RPC URLs; `Debug` shows only their names.
+    pub rpc_secrets: RpcSecrets,
+    /// Which Chainlist RPCs are used, by declared tracking and open source status.
+    pub rpc_policy: RpcPolicy,
+}
+
+#[derive(Debug, Clone)]
+pub struct RedisConfig {
+    pub url: String,
+    pub key_prefix: String,
+    pub ttl: Duration,
+}
+
+/// Cross-origin requests allowed on `/rpc`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Cors {
+    /// Any origin (`APP_ENV=development`, or `*` among the origins).
+    Permissive,
+    /// Only these origins; none by default.
+    Origins(Vec<String>),
+}
+
+#[derive(Debug, Error)]
+pub enum ConfigError {
+    #[error("failed to read config file {path}: {source}")]
+    Read {
+        path: PathBuf,
+        source: std::io::Error,
+    },
+    #[error("invalid config file {path}: {source}")]
+    Parse {
+        path: PathBuf,
+        source: Box<toml::de::Error>,
+    },
+    #[error("{env} (or `{key}` in the config file) must be set")]
+    Missing { env: &'static str, key: String },
+    #[error("invalid {name} {value:?}: {reason}")]
+    Invalid {
+        name: String,
+        value: String,
+        reason: String,
+    },
+}
+
+/// Every setting as read from the file, later overlaid with the environment.
+#[derive(Debug, Default, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct Settings {
+    host: Option<String>,
+    port: Option<u16>,
+    workers: Option<usize>,
+    database_url: Option<String>,
+    app_env: Option<String>,
+    cors_origins: Option<Vec<String>>,
+    chainlist_url: Option<String>,
+    chainlist_sources: Option<Vec<String>>,
+    chainlist_ttl_secs: Option<u64>,
+    chainlist_snapshot_path: Option<PathBuf>,
+    chainlist_bundled_snapshot: Option<bool>,
+    provider_cache_ttl_secs: Option<u64>,
+    fallback_active_cap: Option<usize>,
+    negative_cache_ttl_secs: Option<u64>,
+    token_refresh_interval_secs: Option<u64>,
+    token_max_age_secs: Option<u64>,
+    token_refresh_batch_size: Option<usize>,
+    token_refresh_retry_secs: Option<u64>,
+    token_cache_capacity: Option<usize>,
+    redis_url: Option<String>,
+    redis_key_prefix: Option<String>,
+    redis_cache_ttl_secs: Option<u64>,
+    rpc_overrides: Option<BTreeMap<String, RpcOverride>>,
+    rpc_secrets: Option<HashMap<String, String>>,
+    rpc_tracking: Option<TrackingPolicy>,
+    rpc_open_source_only: Option<bool>,
+}
+
+/// Replaces `field` with env var `name` when it is set.
+fn overlay<T>(
+    env: &impl Fn(&str) -> Option<String>,
+    name: &'static str,
+    field: &mut Option<T>,
+) -> Result<(), ConfigError>
+where
+    T: FromStr,
+    T::Err: std::fmt::Display,
+{
+    if let Some(value) = env(name) {
+        *field = Some(value.parse().map_err(|e: T::Err| ConfigError::Invalid {
+            name: name.to_string(),
+            value: value.clone(),
+            reason: e.to_string(),
+        })?);
+    }
+    Ok(())
+}
+
+fn required<T>(value: Option<T>, env: &'static str) -> Result<T, ConfigError> {
+    value.ok_or_else(|| ConfigError::Missing {
+        env,
+        key: env.to_lowercase(),
+    })
+}
+
+fn invalid(name: &str, value: impl ToString, reason: &str) -> ConfigError {
+    ConfigError::Invalid {
+        name: name.to_string(),
+        value: value.to_string(),
+        reason: reason.to_string(),
+    }
+}
+
+fn comma_separated(value: &str) -> Vec<String> {
+    value
+        .split(',')
+        .map(str::trim)
+        .filter(|item| !item.is_empty())
+        .map(str::to_owned)
+        .collect()
+}
+
+fn secs(value: Option<u64>, default: Duration) -> Duration {
+    value.map_or(default, Duration::from_secs)
+}
+
+impl Settings {
+    fn overlay_env(&mut self, env: &impl Fn(&str) -> Option<String>) -> Result<(), ConfigError> {
+        overlay(env, "HOST", &mut self.host)?;
+        overlay(env, "PORT", &mut self.port)?;
+        overlay(env, "WORKERS", &mut self.workers)?;
+        overlay(env, "DATABASE_URL", &mut self.database_url)?;
+        overlay(env, "APP_ENV", &mut self.app_env)?;
+        if let Some(origins) = env("CORS_ORIGINS") {
+            self.cors_origins = Some(comma_separated(&origins));
+        }
+        // Either one from the environment replaces both from the file.
+        match (env("CHAINLIST_URL"), env("CHAINLIST_SOURCES")) {
+            (Some(_), Some(_)) => {
+                return Err(invalid(
+                    "CHAINLIST_SOURCES",
+                    "",
+                    "set either CHAINLIST_URL or CHAINLIST_SOURCES",
+                ));
+            }
+            (Some(url), None) => (self.chainlist_url, self.chainlist_sources) = (Some(url), None),
+            (None, Some(sources)) => {
+                (self.chainlist_url, self.chainlist_sources) =
+                    (None, Some(comma_separated(&sources)));
+            }
+            (None, None) => {}
+        }
+        overlay(env, "CHAINLIST_TTL_SECS", &mut self.chainlist_ttl_secs)?;
+        overlay(
+            env,
+            "CHAINLIST_SNAPSHOT_PATH",
+            &mut self.chainlist_snapshot_path,
+        )?;
+        overlay(
+            env,
+            "CHAINLIST_BUNDLED_SNAPSHOT",
+            &mut self.chainlist_bundled_snapshot,
+        )?;
+        overlay(
+            env,
+            "PROVIDER_CACHE_TTL_SECS",
+            &mut self.provider_cache_ttl_secs,
+        )?;
+        overlay(env, "FALLBACK_ACTIVE_CAP", &mut self.fallback_active_cap)?;
+        overlay(
+            env,
+            "NEGATIVE_CACHE_TTL_SECS",
+            &mut self.negative_cache_ttl_secs,
+        )?;
+        overlay(
+            env,
+            "TOKEN_REFRESH_INTERVAL_SECS",
+            &mut self.token_refresh_interval_secs,
+        )?;
+        overlay(env, "TOKEN_MAX_AGE_SECS", &mut self.token_max_age_secs)?;
+        overlay(
+            env,
+            "TOKEN_REFRESH_BATCH_SIZE",
+            &mut self.token_refresh_batch_size,
+        )?;
+        overlay(
+            env,
+            "TOKEN_REFRESH_RETRY_SECS",
+            &mut self.token_refresh_retry_secs,
+        )?;
+        overlay(env, "TOKEN_CACHE_CAPACITY", &mut self.token_cache_capacity)?;
+        overlay(env, "REDIS_URL", &mut self.redis_url)?;
+        overlay(env, "REDIS_KEY_PREFIX", &mut self.redis_key_prefix)?;
+        overlay(env, "REDIS_CACHE_TTL_SECS", &mut self.redis_cache_ttl_secs)?;
+        overlay(env, "RPC_TRACKING", &mut self.rpc_tracking)?;
+        overlay(env, "RPC_OPEN_SOURCE_ONLY", &mut self.rpc_open_source_only)?;
+        if let Some(secrets) = env("RPC_SECRETS") {
+            self.rpc_secrets = Some(parse_rpc_secrets(&secrets)?);
+        }
+        Ok(())
+    }
+
+    fn validate(self) -> Result<Config, ConfigError> {
+        let workers = required(self.workers, "WORKERS")?;
+        if workers == 0 {
+            return Err(invalid("WORKERS", workers, "must be at least 1"));
+        }
+
+        let chainlist_sources = match (self.chainlist_url, self.chainlist_sources) {
+            (Some(_), Some(_)) => {
+                return Err(invalid(
+                    "CHAINLIST_SOURCES",
+                    "",
+                    "set either CHAINLIST_URL or CHAINLIST_SOURCES",
+                ));
+            }
+            (_, Some(sources)) if sources.is_empty() => {
+                return Err(invalid(
+                    "CHAINLIST_SOURCES",
+                    "",
+                    "needs at least one source",
+                ));
+            }
+            (_, Some(sources)) => sources,
+            (url, None) => vec![url.unwrap_or_else(|| CHAINLIST_API_URL.to_string())],
+        };
+        let chainlist_sources = chainlist_sources
+            .into_iter()
+            .map(|source| {
+                source
+                    .parse::<ChainlistSource>()
+                    .map_err(|reason| invalid("CHAINLIST_SOURCES", &source, &reason))
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+
+        let fallback_active_cap = self.fallback_active_cap.unwrap_or(FALLBACK_ACTIVE_CAP);
+        let fallback_active_cap = NonZeroUsize::new(fallback_active_cap)
+            .ok_or_else(|| invalid("FALLBACK_ACTIVE_CAP", 0, "must be at least 1"))?;
+
+        let token_refresh_interval = secs(self.token_refresh_interval_secs, TOKEN_REFRESH_INTERVAL);
+        if token_refresh_interval.is_zero() {
+            return Err(invalid(
+                "TOKEN_REFRESH_INTERVAL_SECS",
+                0,
+                "must be at least 1",
+            ));
+        }
+
+        let cors = match self.cors_origins {
+            _ if matches!(self.app_env.as_deref(), Some("development" | "dev")) => Cors::Permissive,
+            Some(origins) if origins.iter().any(|origin| origin == "*") => Cors::Permissive,
+            origins => Cors::Origins(origins.unwrap_or_default()),
+        };
+
+        let redis_ttl = secs(self.redis_cache_ttl_secs, REDIS_CACHE_TTL);
+        if redis_ttl.is_zero() && self.redis_url.is_some() {
+            return Err(invalid("REDIS_CACHE_TTL_SECS", 0, "must be at least 1"));
+        }
+        let redis = self.redis_url.map(|url| RedisConfig {
+            url,
+            key_prefix: self
+                .redis_key_prefix
+                .unwrap_or_else(|| DEFAULT_KEY_PREFIX.to_string()),
+            ttl: redis_ttl,
+        });
+
+        let rpc_overrides = rpc_overrides(self.rpc_overrides.unwrap_or_default())?;
+
+        Ok(Config {
+            host: required(self.host, "HOST")?,
+            port: required(self.port, "PORT")?,
+            workers,
+            database_url: required(self.database_url, "DATABASE_URL")?,
+            chainlist_sources,
+            chainlist_ttl: secs(self.chainlist_ttl_secs, CHAINLIST_TTL),
+            chainlist_snapshot_path: self.chainlist_snapshot_path,
+            chainlist_bundled_snapshot: self.chainlist_bundled_snapshot.unwrap_or(true),
+            provider_cache_ttl: secs(self.provider_cache_ttl_secs, PROVIDER_CACHE_TTL),
+            fallback_active_cap,
+            negative_cache_ttl: secs(self.negative_cache_ttl_secs, NEGATIVE_CACHE_TTL),
+            token_refresh_interval,
+            token_max_age: secs(self.token_max_age_secs, TOKEN_MAX_AGE),
+            token_refresh_batch_size: self
+                .token_refresh_batch_size
+                .unwrap_or(TOKEN_REFRESH_BATCH_SIZE),
+            token_refresh_retry: secs(self.token_refresh_retry_secs, TOKEN_REFRESH_RETRY),
+            token_cache_capacity: NonZeroUsize::new(
+                self.token_cache_capacity.unwrap_or(TOKEN_CACHE_CAPACITY),
+            ),
+            redis,
+            cors,
+            rpc_overrides,
+            rpc_secrets: RpcSecrets::new(self.rpc_secrets.unwrap_or_default()),
+            rpc_policy: RpcPolicy {
+                tracking: self.rpc_tracking.unwrap_or_default(),
+                open_source_only: self.rpc_open_source_only.unwrap_or(false),
+            },
+        })
+    }
+}
+
+/// `NAME=value` pairs separated by commas. Errors name the malformed entry's position, not its
+/// contents.
+fn parse_rpc_secrets(secrets: &str) -> Result<HashMap<String, String>, ConfigError> {
+    secrets
+        .split(',')
+        .map(str::trim)
+        .filter(|entry| !entry.is_empty())
+        .enumerate()
+        .map(|(i, entry)| match entry.split_once('=') {
+            Some((name, value)) if !name.trim().is_empty() => {
+                Ok((name.trim().to_string(), value.trim().to_string()))
+            }
+            _ => Err(invalid(
+                "RPC_SECRETS",
+                format!("entry {}", i + 1),
+                "expected NAME=value",
+            )),
+        })
+        .collect()
+}
+
+/// Keys the overrides by chain id and checks every endpoint. Errors show at most an endpoint's
+/// origin, never its full URL or header values.
+fn rpc_overrides(
+    overrides: BTreeMap<String, RpcOverride>,
+) -> Result<HashMap<ChainId, RpcOverride>, ConfigError> {
+    overrides
+        .into_iter()
+        .map(|(chain_id, rpc_override)| {
+            let name = format!("rpc_overrides.{chain_id}");
+            let chain_id: ChainId = chain_id
+                .parse()
+                .map_err(|_| invalid("rpc_overrides key", &chain_id, "must be a chain id"))?;
+            if rpc_override.endpoints.is_empty() {
+                return Err(invalid(&name, "[]", "needs at least one endpoint"));
+            }
+            for endpoint in &rpc_override.endpoints {
+                if url::Url::parse(&endpoint.url).is_err() {
+                    return Err(invalid(
+                        &name,
+                        endpoint.redacted_url(),
+                        "endpoint URL is not valid",
+                    ));
+                }
+                if let Err(e) = endpoint.header_map() {
+                    return Err(invalid(&name, endpoint.redacted_url(), &e.to_string()));
+                }
+            }
+            Ok((chain_id, rpc_override))
+        })
+        .collect()
+}
+
+impl Config {
+    /// Loads the file named by `CONFIG_FILE` (if set) and overlays the process environment.
+    pub fn load() -> Result<Self, ConfigError> {
+        let env = |name: &str| env::var(name).ok();
+        let file = env("CONFIG_FILE")
+            .map(|path| {
+                let path = PathBuf::from(path);
+                match fs::read_to_string(&path) {
+                    Ok(contents) => Ok((path, contents)),
+                    Err(source) => Err(ConfigError::Read { path, source }),
+                }
+            })
+            .transpose()?;
+        Self::from_sources(file, env)
+    }
+
+    /// Config from a TOML `file` (path, contents) overlaid with variables from `env`.
+    pub fn from_sources(
+        file: Option<(PathBuf, String)>,
+        env: impl Fn(&str) -> Option<String>,
+    ) -> Result<Self, ConfigError> {
+        let mut settings = match file {
+            Some((path, contents)) => {
+                toml::from_str(&contents).map_err(|source| ConfigError::Parse {
+                    path,
+                    source: Box::new(source),
+                })?
+            }
+            None => Settings::default(),
+        };
+        settings.overlay_env(&env)?;
+        settings.validate()
+    }
+
+    pub fn repository_backend(&self) -> RepositoryBackend {
+        RepositoryBackend::for_url(&self.database_url)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::services::provider::RpcOverrideMode;
+
+    fn env(vars: &[(&str, &str)]) -> impl Fn(&str) -> Option<String> {
+        let vars: HashMap<String, String> = vars
+            .iter()
+            .map(|(name, value)| (name.to_string(), value.to_string()))
+            .collect();
+        move |name| vars.get(name).cloned()
+    }
+
+    const REQUIRED: &[(&str, &str)] = &[
+        ("HOST", "127.0.0.1"),
+        ("PORT", "8080"),
+        ("WORKERS", "2"),
+        ("DATABASE_URL", "db/tokens.db"),
+    ];
+
+    fn file(contents: &str) -> Option<(PathBuf, String)> {
+        Some((PathBuf::from("token-api.toml"), contents.to_string()))
+    }
+
+    #[test]
+    fn defaults_apply_when_only_required_settings_are_given() {
+        let config = Config::from_sources(None, env(REQUIRED)).unwrap();
+        assert_eq!(config.port, 8080);
+        assert_eq!(
+            config.chainlist_sources,
+            [ChainlistSource::Url(CHAINLIST_API_URL.to_string())]
+        );
+        assert_eq!(config.chainlist_ttl, CHAINLIST_TTL);
+        assert_eq!(config.chainlist_snapshot_path, None);
+        assert!(config.chainlist_bundled_snapshot);
+        assert_eq!(config.fallback_active_cap.get(), FALLBACK_ACTIVE_CAP);
+        assert_eq!(config.token_cache_capacity, NonZeroUsize::new(10_000));
+        assert_eq!(config.cors, Cors::Origins(vec![]));
+        assert!(config.redis.is_none());
+        assert!(config.rpc_overrides.is_empty());
+        assert!(config.rpc_secrets.is_empty());
+        assert_eq!(config.rpc_policy, RpcPolicy::default());
+        assert_eq!(config.repository_backend(), RepositoryBackend::Sqlite);
+    }
+
+    #[test]
+    fn environment_overrides_the_file() {
+        let contents = r#"
+            host = "0.0.0.0"
+            port = 9000
+            workers = 4
+            database_url = "postgres://localhost/tokens"
+            fallback_active_cap = 8
+            cors_origins = ["https://app.example"]
+            redis_url = "redis://localhost"
+            chainlist_snapshot_path = "/data/chainlist.json"
+        "#;
+        let config = Config::from_sources(
+            file(contents),
+            env(&[
+                ("PORT", "9100"),
+                ("CHAINLIST_TTL_SECS", "60"),
+                ("CHAINLIST_BUNDLED_SNAPSHOT", "false"),
+                ("CORS_ORIGINS", "https://a.example, https://b.example"),
+            ]),
+        )
+        .unwrap();
+
+        assert_eq!(config.host, "0.0.0.0");
+        assert_eq!(config.port, 9100);
+        assert_eq!(config.fallback_active_cap.get(), 8);
+        assert_eq!(config.chainlist_ttl, Duration::from_secs(60));
+        assert_eq!(
+            config.chainlist_snapshot_path,
+            Some(PathBuf::from("/data/chainlist.json"))
+        );
+        assert!(!config.chainlist_bundled_snapshot);
+        assert_eq!(
+            config.cors,
+            Cors::Origins(vec![
+                "https://a.example".to_string(),
+                "https://b.example".to_string()
+            ])
+        );
+        let redis = config.redis.as_ref().expect("redis configured");
+        assert_eq!(redis.key_prefix, "token-api");
+        assert_eq!(config.repository_backend(), RepositoryBackend::Postgres);
+    }
+
+    #[test]
+    fn development_or_wildcard_allows_any_origin() {
+        let mut vars = REQUIRED.to_vec();
+        vars.push(("APP_ENV", "development"));
+        let config = Config::from_sources(None, env(&vars)).unwrap();
+        assert_eq!(config.cors, Cors::Permissive);
+
+        let config = Config::from_sources(file(r#"cors_origins = ["*"]"#), env(REQUIRED)).unwrap();
+        assert_eq!(config.cors, Cors::Permissive);
+    }
+
+    #[test]
+    fn rpc_overrides_are_read_per_chain() {
+        let contents = r#"
+            [rpc_overrides.1]
+            mode = "replace"
+            endpoints = [
+                "https://eth.example/v2/secret-key",
+                { url = "https://private.example/rpc", headers = { Authorization = "Bearer secret-token" } },
+            ]
+
+            [rpc_overrides.10]
+            endpoints = ["https://op.example"]
+        "#;
+        let config = Config::from_sources(file(contents), env(REQUIRED)).unwrap();
+
+        let mainnet = &config.rpc_overrides[&1];
+        assert_eq!(mainnet.mode, RpcOverrideMode::Replace);
+        assert_eq!(
+            mainnet.endpoints[0].url,
+            "https://eth.example/v2/secret-key"
+        );
+        assert!(mainnet.endpoints[0].headers.is_empty());
+        assert_eq!(
+            mainnet.endpoints[1].headers["Authorization"],
+            "Bearer secret-token"
+        );
+        assert_eq!(config.rpc_overrides[&10].mode, RpcOverrideMode::Prepend);
+
+        let debug = format!("{config:?}");
+        assert!(!debug.contains("secret-"), "{debug}");
+        assert!(debug.contains("https://private.example"), "{debug}");
+    }
+
+    #[test]
+    fn invalid_rpc_overrides_are_rejected_without_leaking_secrets() {
+        let err = Config::from_sources(
+            file("[rpc_overrides.mainnet]\nendpoints = [\"https://eth.example\"]"),
+            env(REQUIRED),
+        )
+        .unwrap_err();
+        assert!(err.to_string().contains("must be a chain id"), "{err}");
+
+        let err = Config::from_sources(file("[rpc_overrides.1]\nendpoints = []"), env(REQUIRED))
+            .unwrap_err();
+        assert!(err.to_string().contains("at least one endpoint"), "{err}");
+
+        let contents = r#"
+            [rpc_overrides.1]
+            endpoints = [{ url = "https://eth.example/secret-key", headers = { "Bad Header" = "secret-token" } }]
+        "#;
+        let err = Config::from_sources(file(contents), env(REQUIRED)).unwrap_err();
+        let message = err.to_string();
+        assert!(
+            message.contains("invalid RPC header Bad Header"),
+            "{message}"
+        );
+        assert!(!message.contains("secret"), "{message}");
+
+        let err = Config::from_sources(
+            file("[rpc_overrides.1]\nmode = \"overwrite\"\nendpoints = [\"https://eth.example\"]"),
+            env(REQUIRED),
+        )
+        .unwrap_err();
+        assert!(err.to_string().contains("overwrite"), "{err}");
+    }
+
+    #[test]
+    fn rpc_secrets_come_from_file_or_environment() {
+        let contents = r#"
+            [rpc_secrets]
+            INFURA_API_KEY = "from-file"
+        "#;
+        let config = Config::from_sources(file(contents), env(REQUIRED)).unwrap();
+        assert_eq!(
+            config
+                .rpc_secrets
+                .expand("https://x/${INFURA_API_KEY}")
+                .as_deref(),
+            Some("https://x/from-file")
+        );
+
+        let mut vars = REQUIRED.to_vec();
+        vars.push((
+            "RPC_SECRETS",
+            "INFURA_API_KEY=from-env, ALCHEMY_API_KEY=a=b",
+        ));
+        let config = Config::from_sources(file(contents), env(&vars)).unwrap();
+        assert_eq!(
+            config
+                .rpc_secrets
+                .expand("https://x/${INFURA_API_KEY}/${ALCHEMY_API_KEY}")
+                .as_deref(),
+            Some("https://x/from-env/a=b")
+        );
+        assert!(!format!("{config:?}").contains("from-env"));
+
+        let mut vars = REQUIRED.to_vec();
+        vars.push(("RPC_SECRETS", "INFURA_API_KEY=secret,oops"));
+        let err = Config::from_sources(None, env(&vars)).unwrap_err();
+        assert!(err.to_string().contains("entry 2"), "{err}");
+        assert!(!err.to_string().contains("secret"), "{err}");
+    }
+
+    #[test]
+    fn chainlist_sources_are_urls_or_files_in_priority_order() {
+        let contents = r#"
+            chainlist_sources = ["config/rpcs.json", "https://chainlist.org/rpcs.json"]
+        "#;
+        let config = Config::from_sources(file(contents), env(REQUIRED)).unwrap();
+        assert_eq!(
+            config.chainlist_sources,
+            [
+                ChainlistSource::File(PathBuf::from("config/rpcs.json")),
+                ChainlistSource::Url("https://chainlist.org/rpcs.json".to_string()),
+            ]
+        );
+
+        let mut vars = REQUIRED.to_vec();
+        vars.push(("CHAINLIST_URL", "file:///srv/rpcs.json"));
+        let config = Config::from_sources(file(contents), env(&vars)).unwrap();
+        assert_eq!(
+            config.chainlist_sources,
+            [ChainlistSource::File(PathBuf::from("/srv/rpcs.json"))]
+        );
+
+        vars.push(("CHAINLIST_SOURCES", "a.json,b.json"));
+        let err = Config::from_sources(None, env(&vars)).unwrap_err();
+        assert!(err.to_string().contains("set either"), "{err}");
+
+        let both = r#"
+            chainlist_url = "https://chainlist.org/rpcs.json"
+            chainlist_sources = ["config/rpcs.json"]
+        "#;
+        assert!(Config::from_sources(file(both), env(REQUIRED)).is_err());
+
+        let mut vars = REQUIRED.to_vec();
+        vars.push(("CHAINLIST_SOURCES", "ftp://example.com/rpcs.json"));
+        let err = Config::from_sources(None, env(&vars)).unwrap_err();
+        assert!(err.to_string().contains("unsupported scheme ftp"), "{err}");
+    }
+
+    #[test]
+    fn rpc_policy_is_read_from_file_and_environment() {
+        let contents = r#"
+            rpc_tracking = "limited"
+            rpc_open_source_only = true
+        "#;
+        let config = Config::from_sources(file(contents), env(REQUIRED)).unwrap();
+        assert_eq!(config.rpc_policy.tracking, TrackingPolicy::Limited);
+        assert!(config.rpc_policy.open_source_only);
+
+        let mut vars = REQUIRED.to_vec();
+        vars.push(("RPC_TRACKING", "none"));
+        let config = Config::from_sources(file(contents), env(&vars)).unwrap();
+        assert_eq!(config.rpc_policy.tracking, TrackingPolicy::None);
+
+        let mut vars = REQUIRED.to_vec();
+        vars.push(("RPC_TRACKING", "never"));
+        let err = Config::from_sources(None, env(&vars)).unwrap_err();
+        assert!(
+            err.to_string().contains("expected none, limited or any"),
+            "{err}"
+        );
+    }
+
+    #[test]
+    fn errors_name_the_offending_setting() {
+        let err = Config::from_sources(None, env(&REQUIRED[..3])).unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "DATABASE_URL (or `database_url` in the config file) must be set"
+        );
+
+        let mut vars = REQUIRED.to_vec();
+        vars.push(("FALLBACK_ACTIVE_CAP", "many"));
+        let err = Config::from_sources(None, env(&vars)).unwrap_err();
+        assert!(
+            err.to_string()
+                .starts_with("invalid FALLBACK_ACTIVE_CAP \"many\""),
+            "{err}"
+        );
+
+        let err = Config::from_sources(file("prot = 8080"), env(REQUIRED)).unwrap_err();
+        assert!(err.to_string().contains("unknown field `prot`"), "{err}");
+
+        let mut vars = REQUIRED.to_vec();
+        vars.push(("TOKEN_REFRESH_INTERVAL_SECS", "0"));
+        assert!(Config::from_sources(None, env(&vars)).is_err());
+
+        let mut vars = REQUIRED.to_vec();
+        vars.extend([
+            ("REDIS_URL", "redis://localhost"),
+            ("REDIS_CACHE_TTL_SECS", "0"),
+        ]);
+        let err = Config::from_sources(None, env(&vars)).unwrap_err();
+        assert!(
+            err.to_string()
+                .starts_with("invalid REDIS_CACHE_TTL_SECS \"0\""),
+            "{err}"
+        );
+
+        // Without Redis the TTL is unused.
+        let mut vars = REQUIRED.to_vec();
+        vars.push(("REDIS_CACHE_TTL_SECS", "0"));
+        assert!(
+            Config::from_sources(None, env(&vars))
+                .unwrap()
+                .redis
+                .is_none()
+        );
+    }
+}
@@ -1,16 +1,25 @@
+use std::{
+    collections::{BTreeMap, HashMap},
+    sync::Arc,
+};
+
 use actix_web::{HttpResponse, Responder, get};
 use alloy::primitives::Address;
 use alloy::rpc::client::RpcClient;
-use jsonrpc_v2::Params;
+use futures::future::join_all;
+use jsonrpc_v2::{MapRouter, Params, Server};
 use log::{debug, error};
-use serde::Deserialize;
+use serde::{Deserialize, Serialize};
 
 use crate::{
+    chainlist::{Explorer, NativeCurrency},
+    native,
+    rpc_error::{INTERNAL_ERROR, RpcError},
     services::{
         evm::EvmTokenService,
-        provider::{ProviderService, ProviderServiceError},
+        provider::{ProviderService, SupportedChain},
     },
-    token::Token,
+    token::{Token, TokenAddress},
     types::ChainId,
 };
 
@@ -25,13 +34,20 @@ pub async fn get_evm_token_metadata(
     evm_token_service: jsonrpc_v2::Data<EvmTokenService>,
     provider_service: jsonrpc_v2::Data<ProviderService>,
 ) -> Result<Token, jsonrpc_v2::Error> {
+    let address = match parse_address(&params)? {
+        TokenAddress::Contract(address) => address,
+        TokenAddress::Native(coin_type) => {
+            return Ok(get_native_token(&provider_service, params.chain_id, coin_type).await?);
+        }
+    };
+
     let rpc = provider_service
         .rpc_client_for_chain(params.chain_id)
         .await
-        .map_err(provider_error_to_jsonrpc)?
-        .ok_or_else(|| format!("No RPC URLs for chain {}", params.chain_id))?;
+        .map_err(|e| RpcError::from(&e))?
+        .ok_or_else(|| RpcError::unknown_chain(params.chain_id))?;
 
-    get_evm_token_metadata_with_rpc_client(params, rpc, evm_token_service).await
+    get_evm_token_metadata_with_rpc_client(params.chain_id, address, rpc, evm_token_service).await
 }
 
 #[derive(Deserialize)]
@@ -44,58 +60,650 @@ pub struct GetEvmTokenMetadataParamsWithRpcUrl {
 pub async fn get_evm_token_metadata_with_rpc_url(
     Params(params): Params<GetEvmTokenMetadataParamsWithRpcUrl>,
     evm_token_service: jsonrpc_v2::Data<EvmTokenService>,
+    provider_service: jsonrpc_v2::Data<ProviderService>,
 ) -> Result<Token, jsonrpc_v2::Error> {
     let url = params
         .rpc_url
         .parse::<reqwest::Url>()
-        .map_err(|e| e.to_string())?;
+        .map_err(|e| RpcError::invalid_param("rpc_url", format!("Invalid RPC URL: {e}")))?;
+
+    let address = match TokenAddress::parse(params.chain_id, &params.address) {
+        Some(TokenAddress::Contract(address)) => address,
+        Some(TokenAddress::Native(coin_type)) => {
+            return Ok(get_native_token(&provider_service, params.chain_id, coin_type).await?);
+        }
+        None => return Err(RpcError::invalid_param("address", "Invalid EVM address").into()),
+    };
 
     let rpc = RpcClient::new_http(url);
 
-    get_evm_token_metadata_with_rpc_client(
-        GetEvmTokenMetadata {
-            chain_id: params.chain_id,
-            address: params.address,
-        },
-        rpc,
-        evm_token_service,
-    )
-    .await
+    get_evm_token_metadata_with_rpc_client(params.chain_id, address, rpc, evm_token_service).await
+}
+
+fn parse_address(params: &GetEvmTokenMetadata) -> Result<TokenAddress, RpcError> {
+    TokenAddress::parse(params.chain_id, &params.address)
+        .ok_or_else(|| RpcError::invalid_param("address", "Invalid EVM address"))
 }
 
 async fn get_evm_token_metadata_with_rpc_client(
-    params: GetEvmTokenMetadata,
+    chain_id: ChainId,
+    address: Address,
     rpc: RpcClient,
     evm_token_service: jsonrpc_v2::Data<EvmTokenService>,
 ) -> Result<Token, jsonrpc_v2::Error> {
-    let chain_id = params.chain_id;
-    let evm_address = params.address;
-
     debug!("Chain ID: {:?}", chain_id);
-    debug!("EVM address: {:?}", evm_address);
-
-    let Ok(checked_address) = evm_address.parse::<Address>() else {
-        return Err("Invalid EVM address".into());
-    };
+    debug!("EVM address: {:?}", address);
 
     let token = evm_token_service
-        .get_or_fetch_token(chain_id, checked_address, rpc)
+        .get_or_fetch_token(chain_id, address, rpc)
         .await;
 
     match token {
         Ok(token) => Ok(token),
         Err(e) => {
             error!("Error getting EVM token: {:?}", e);
-            Err(e.into())
+            Err(RpcError::from(&e).into())
         }
     }
 }
 
-fn provider_error_to_jsonrpc(e: ProviderServiceError) -> jsonrpc_v2::Error {
-    e.to_string().into()
+/// Chain `chain_id`'s native currency from Chainlist. `coin_type` is the SLIP-44 coin type the
+/// client asked by, which must be the chain's.
+async fn get_native_token(
+    provider_service: &ProviderService,
+    chain_id: ChainId,
+    coin_type: Option<u32>,
+) -> Result<Token, RpcError> {
+    let chain = provider_service
+        .chain_metadata(chain_id)
+        .await
+        .map_err(|e| RpcError::from(&e))?;
+    let Some((token, chain)) = chain.and_then(|chain| Some((native::native_token(&chain)?, chain)))
+    else {
+        return Err(RpcError::unknown_native_currency(chain_id));
+    };
+    if let Some(coin_type) = coin_type
+        && coin_type != native::slip44(&chain)
+    {
+        return Err(RpcError::invalid_param(
+            "address",
+            format!("Native asset of chain {chain_id} is {}", token.id),
+        ));
+    }
+    Ok(token)
+}
+
+/// Upper bound on items in one `eth_getTokensMetadata` request.
+const MAX_BATCH_TOKENS: usize = 1000;
+
+#[derive(Deserialize)]
+pub struct GetEvmTokensMetadata {
+    tokens: Vec<GetEvmTokenMetadata>,
+}
+
+/// One entry of an `eth_getTokensMetadata` response, in request order: exactly one of `token` or
+/// `error` is set.
+#[derive(Serialize)]
+pub struct TokenMetadataResult {
+    chain_id: ChainId,
+    address: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    token: Option<Token>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    error: Option<RpcError>,
+}
+
+/// Resolves many tokens at once. Items are grouped by chain so each chain's cache misses share
+/// Multicall3 round trips, and chains are resolved concurrently; failures are reported per item
+/// instead of failing the whole request.
+pub async fn get_evm_tokens_metadata(
+    Params(params): Params<GetEvmTokensMetadata>,
+    evm_token_service: jsonrpc_v2::Data<EvmTokenService>,
+    provider_service: jsonrpc_v2::Data<ProviderService>,
+) -> Result<Vec<TokenMetadataResult>, jsonrpc_v2::Error> {
+    if params.tokens.len() > MAX_BATCH_TOKENS {
+        return Err(RpcError::invalid_param(
+            "tokens",
+            format!("Too many tokens: at most {MAX_BATCH_TOKENS} per request"),
+        )
+        .into());
+    }
+
+    let parsed: Vec<Option<TokenAddress>> = params
+        .tokens
+        .iter()
+        .map(|t| TokenAddress::parse(t.chain_id, &t.address))
+        .collect();
+
+    let mut by_chain: BTreeMap<ChainId, Vec<Address>> = BTreeMap::new();
+    for (item, address) in params.tokens.iter().zip(&parsed) {
+        if let Some(TokenAddress::Contract(address)) = address {
+            by_chain.entry(item.chain_id).or_default().push(*address);
+        }
+    }
+
+    let chain_results = join_all(by_chain.into_iter().map(|(chain_id, addresses)| {
+        let (evm_token_service, provider_service) = (&evm_token_service, &provider_service);
+        async move {
+            let result = match provider_service.rpc_client_for_chain(chain_id).await {
+                Ok(Some(rpc)) => evm_token_service
+                    .get_or_fetch_tokens(chain_id, &addresses, rpc)
+                    .await
+                    .map_err(|e| RpcError::from(&e)),
+                Ok(None) => Err(RpcError::unknown_chain(chain_id)),
+                Err(e) => Err(RpcError::from(&e)),
+            };
+            (chain_id, addresses, result)
+        }
+    }))
+    .await;
+
+    let mut outcomes: HashMap<(ChainId, Address), Result<Token, RpcError>> = HashMap::new();
+    for (chain_id, addresses, chain_result) in chain_results {
+        match chain_result {
+            Ok(results) => {
+                for (address, result) in results {
+                    let result = result.map_err(|e| {
+                        error!("Error getting EVM token {address} on chain {chain_id}: {e:?}");
+                        RpcError::from(&e)
+                    });
+                    outcomes.insert((chain_id, address), result);
+                }
+            }
+            Err(e) => {
+                error!(
+                    "Error getting EVM tokens on chain {chain_id}: {}",
+                    e.message
+                );
+                for address in addresses {
+                    outcomes.insert((chain_id, address), Err(e.clone()));
+                }
+            }
+        }
+    }
+
+    let mut results = Vec::with_capacity(params.tokens.len());
+    for (item, address) in params.tokens.into_iter().zip(parsed) {
+        let outcome = match address {
+            Some(TokenAddress::Contract(address)) => outcomes
+                .get(&(item.chain_id, address))
+                .cloned()
+                .unwrap_or_else(|| Err(RpcError::new(INTERNAL_ERROR, "Token not resolved"))),
+            Some(TokenAddress::Native(coin_type)) => {
+                get_native_token(&provider_service, item.chain_id, coin_type).await
+            }
+            None => Err(RpcError::invalid_param("address", "Invalid EVM address")),
+        };
+        let (token, error) = match outcome {
+            Ok(token) => (Some(token), None),
+            Err(e) => (None, Some(e)),
+        };
+        results.push(TokenMetadataResult {
+            chain_id: item.chain_id,
+            address: item.address,
+            token,
+            error,
+        });
+    }
+    Ok(results)
+}
+
+/// A chain tokens can be resolved on. Metadata comes from Chainlist and is absent for chains only
+/// served by configured RPCs.
+#[derive(Serialize)]
+pub struct ChainInfo {
+    chain_id: ChainId,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    name: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    short_name: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    native_currency: Option<NativeCurrency>,
+    explorers: Vec<Explorer>,
+}
+
+impl From<SupportedChain> for ChainInfo {
+    fn from(chain: SupportedChain) -> Self {
+        let metadata = chain.metadata;
+        ChainInfo {
+            chain_id: chain.chain_id,
+            name: metadata.as_ref().map(|m| m.name.clone()),
+            short_name: metadata.as_ref().and_then(|m| m.short_name.clone()),
+            native_currency: metadata.as_ref().and_then(|m| m.native_currency.clone()),
+            explorers: metadata.map(|m| m.explorers).unwrap_or_default(),
+        }
+    }
+}
+
+/// Every chain `eth_getTokenMetadata` can resolve tokens on, by chain id.
+pub async fn get_chains(
+    provider_service: jsonrpc_v2::Data<ProviderService>,
+) -> Result<Vec<ChainInfo>, jsonrpc_v2::Error> {
+    let chains = provider_service
+        .supported_chains()
+        .await
+        .map_err(|e| RpcError::from(&e))?;
+    Ok(chains.into_iter().map(ChainInfo::from).collect())
+}
+
+#[derive(Deserialize)]
+pub struct GetChain {
+    chain_id: ChainId,
+}
+
+pub async fn get_chain(
+    Params(params): Params<GetChain>,
+    provider_service: jsonrpc_v2::Data<ProviderService>,
+) -> Result<ChainInfo, jsonrpc_v2::Error> {
+    let chain = provider_service
+        .supported_chain(params.chain_id)
+        .await
+        .map_err(|e| RpcError::from(&e))?
+        .ok_or_else(|| RpcError::unknown_chain(params.chain_id))?;
+    Ok(chain.into())
+}
+
+/// The `/rpc` JSON-RPC server with every method registered.
+pub fn rpc_server(
+    evm_token_service: EvmTokenService,
+    provider_service: ProviderService,
+) -> Arc<Server<MapRouter>> {
+    Server::new()
+        .with_data(jsonrpc_v2::Data::new(evm_token_service))
+        .with_data(jsonrpc_v2::Data::new(provider_service))
+        .with_method(
+            "eth_getTokenMetadataWithRpc",
+            get_evm_token_metadata_with_rpc_url,
+        )
+        .with_method("eth_getTokenMetadata", get_evm_token_metadata)
+        .with_method("eth_getTokensMetadata", get_evm_tokens_metadata)
+        .with_method("chain_getChains", get_chains)
+        .with_method("chain_getChain", get_chain)
+        .finish()
 }
 
 #[get("/")]
 pub async fn hello_world() -> impl Responder {
     HttpResponse::Ok().body("Hello, TokenAPI!")
 }
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use actix_web::{App, guard, test, web};
+    use serde_json::{Value, json};
+    use wiremock::{
+        Mock, MockServer, Request, ResponseTemplate,
+        matchers::{method, path},
+    };
+
+    use super::*;
+    use crate::{
+        repositories::sqlite::{evm_token::SqliteEvmTokenRepository, test_support},
+        rpc_error,
+        services::{
+            chainlist::ChainlistService,
+            provider::{RpcEndpoint, RpcOverride, RpcOverrideMode},
+        },
+    };
+
+    /// Chainlist serving `chain_id` with a single RPC at `rpc_url`.
+    async fn chainlist_mock(chain_id: ChainId, rpc_url: &str) -> MockServer {
+        let list = MockServer::start().await;
+        Mock::given(method("GET"))
+            .and(path("/rpcs.json"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(json!([{
+                "name": "Test",
+                "chain": "TST",
+                "chainId": chain_id,
+                "rpc": [{ "url": rpc_url }],
+            }])))
+            .mount(&list)
+            .await;
+        list
+    }
+
+    /// RPC node answering every request (including `eth_chainId`) with `chain_id`.
+    async fn rpc_mock(chain_id: ChainId) -> MockServer {
+        let rpc = MockServer::start().await;
+        Mock::given(method("POST"))
+            .respond_with(move |req: &Request| {
+                let id = serde_json::from_slice::<Value>(&req.body)
+                    .ok()
+                    .and_then(|v| v.get("id").cloned())
+                    .unwrap_or(json!(0));
+                ResponseTemplate::new(200).set_body_json(json!({
+                    "jsonrpc": "2.0",
+                    "id": id,
+                    "result": format!("0x{chain_id:x}"),
+                }))
+            })
+            .mount(&rpc)
+            .await;
+        rpc
+    }
+
+    fn services(
+        repository: SqliteEvmTokenRepository,
+        chainlist: &MockServer,
+    ) -> (EvmTokenService, ProviderService) {
+        let chainlist = ChainlistService::with_client_and_url(
+            Duration::from_secs(3600),
+            reqwest::Client::new(),
+            format!("{}/rpcs.json", chainlist.uri()),
+        );
+        (
+            EvmTokenService::new(Arc::new(repository), Duration::from_secs(3600)),
+            ProviderService::new(chainlist, Duration::from_secs(3600)),
+        )
+    }
+
+    async fn call_rpc(
+        (evm_token_service, provider_service): (EvmTokenService, ProviderService),
+        rpc_method: &str,
+        params: Value,
+    ) -> Value {
+        let server = rpc_server(evm_token_service, provider_service);
+        let app = test::init_service(
+            App::new().service(
+                web::service("/rpc")
+                    .guard(guard::Post())
+                    .finish(server.into_web_service()),
+            ),
+        )
+        .await;
+        let req = test::TestRequest::post()
+            .uri("/rpc")
+            .set_json(json!({
+                "jsonrpc": "2.0",
+                "id": 1,
+                "method": rpc_method,
+                "params": params,
+            }))
+            .to_request();
+        test::call_and_read_body_json(&app, req).await
+    }
+
+    #[actix_web::test]
+    async fn invalid_address_is_invalid_params() {
+        let rpc = rpc_mock(1).await;
+        let list = chainlist_mock(1, &rpc.uri()).await;
+
+        let body = call_rpc(
+            services(test_support::migrated_repository(), &list),
+            "eth_getTokenMetadata",
+            json!({ "chain_id": 1, "address": "not-an-address" }),
+        )
+        .await;
+
+        assert_eq!(body["error"]["code"], rpc_error::INVALID_PARAMS);
+        assert_eq!(body["error"]["data"]["field"], "address");
+    }
+
+    #[actix_web::test]
+    async fn unknown_chain_has_its_own_code() {
+        let list = chainlist_mock(42, "https://rpc.example/").await;
+
+        let body = call_rpc(
+            services(test_support::migrated_repository(), &list),
+            "eth_getTokenMetadata",
+            json!({ "chain_id": 999, "address": Address::repeat_byte(1).to_string() }),
+        )
+        .await;
+
+        assert_eq!(body["error"]["code"], rpc_error::UNKNOWN_CHAIN);
+        assert_eq!(body["error"]["data"]["chain_id"], 999);
+    }
+
+    #[actix_web::test]
+    async fn chain_id_mismatch_carries_both_chain_ids() {
+        // Chainlist RPCs serving another chain are excluded when probed; a configured one is not.
+        let rpc = rpc_mock(10).await;
+        let list = chainlist_mock(1, "https://rpc.example/").await;
+        let (evm_token_service, provider_service) =
+            services(test_support::migrated_repository(), &list);
+        let provider_service = provider_service.with_rpc_overrides(HashMap::from([(
+            1,
+            RpcOverride {
+                mode: RpcOverrideMode::Replace,
+                endpoints: vec![RpcEndpoint::from(rpc.uri())],
+            },
+        )]));
+
+        let body = call_rpc(
+            (evm_token_service, provider_service),
+            "eth_getTokenMetadata",
+            json!({ "chain_id": 1, "address": Address::repeat_byte(1).to_string() }),
+        )
+        .await;
+
+        assert_eq!(body["error"]["code"], rpc_error::CHAIN_ID_MISMATCH);
+        assert_eq!(
+            body["error"]["data"],
+            json!({ "expected_chain_id": 1, "rpc_chain_id": 10 })
+        );
+    }
+
+    #[actix_web::test]
+    async fn storage_failure_has_its_own_code() {
+        let rpc = rpc_mock(1).await;
+        let list = chainlist_mock(1, &rpc.uri()).await;
+
+        let body = call_rpc(
+            services(test_support::unmigrated_repository(), &list),
+            "eth_getTokenMetadata",
+            json!({ "chain_id": 1, "address": Address::repeat_byte(1).to_string() }),
+        )
+        .await;
+
+        assert_eq!(body["error"]["code"], rpc_error::STORAGE_UNAVAILABLE);
+    }
+
+    #[actix_web::test]
+    async fn chainlist_failure_is_upstream_unavailable() {
+        let list = MockServer::start().await;
+        Mock::given(method("GET"))
+            .and(path("/rpcs.json"))
+            .respond_with(ResponseTemplate::new(503))
+            .mount(&list)
+            .await;
+
+        let body = call_rpc(
+            services(test_support::migrated_repository(), &list),
+            "eth_getTokenMetadata",
+            json!({ "chain_id": 1, "address": Address::repeat_byte(1).to_string() }),
+        )
+        .await;
+
+        assert_eq!(body["error"]["code"], rpc_error::UPSTREAM_UNAVAILABLE);
+        assert_eq!(body["error"]["data"]["source"], "chainlist");
+    }
+
+    #[actix_web::test]
+    async fn chains_are_listed_with_their_metadata() {
+        let list = MockServer::start().await;
+        Mock::given(method("GET"))
+            .and(path("/rpcs.json"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(json!([
+                {
+                    "name": "Ethereum Mainnet",
+                    "chain": "ETH",
+                    "chainId": 1,
+                    "shortName": "eth",
+                    "nativeCurrency": { "name": "Ether", "symbol": "ETH", "decimals": 18 },
+                    "explorers": [{ "name": "etherscan", "url": "https://etherscan.io", "standard": "EIP3091" }],
+                    "rpc": [{ "url": "https://rpc.example/" }],
+                },
+                { "name": "No RPCs", "chain": "NONE", "chainId": 2, "rpc": [] },
+            ])))
+            .mount(&list)
+            .await;
+
+        let body = call_rpc(
+            services(test_support::migrated_repository(), &list),
+            "chain_getChains",
+            json!([]),
+        )
+        .await;
+        assert_eq!(
+            body["result"],
+            json!([{
+                "chain_id": 1,
+                "name": "Ethereum Mainnet",
+                "short_name": "eth",
+                "native_currency": { "name": "Ether", "symbol": "ETH", "decimals": 18 },
+                "explorers": [{ "name": "etherscan", "url": "https://etherscan.io", "standard": "EIP3091" }],
+            }])
+        );
+
+        let body = call_rpc(
+            services(test_support::migrated_repository(), &list),
+            "chain_getChain",
+            json!({ "chain_id": 1 }),
+        )
+        .await;
+        assert_eq!(body["result"]["native_currency"]["symbol"], "ETH");
+
+        let body = call_rpc(
+            services(test_support::migrated_repository(), &list),
+            "chain_getChain",
+            json!({ "chain_id": 2 }),
+        )
+        .await;
+        assert_eq!(body["error"]["code"], rpc_error::UNKNOWN_CHAIN);
+    }
+
+    #[actix_web::test]
+    async fn native_currency_is_resolved_from_chainlist() {
+        // The RPC is never called: native metadata comes from Chainlist alone.
+        let list = MockServer::start().await;
+        Mock::given(method("GET"))
+            .and(path("/rpcs.json"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(json!([
+                {
+                    "name": "Polygon Mainnet",
+                    "chain": "Polygon",
+                    "chainId": 137,
+                    "slip44": 966,
+                    "nativeCurrency": { "name": "POL", "symbol": "POL", "decimals": 18 },
+                    "rpc": [{ "url": "https://rpc.example/" }],
+                },
+                { "name": "No currency", "chain": "NONE", "chainId": 2, "rpc": [] },
+            ])))
+            .mount(&list)
+            .await;
+
+        for address in [
+            "0xEeeeeEeeeEeEeeEeEeEeeEEEeeeeEeeeeeeeEEeE",
+            "0x0000000000000000000000000000000000000000",
+            "slip44:966",
+            "eip155:137/slip44:966",
+        ] {
+            let body = call_rpc(
+                services(test_support::migrated_repository(), &list),
+                "eth_getTokenMetadata",
+                json!({ "chain_id": 137, "address": address }),
+            )
+            .await;
+            let token = &body["result"];
+            assert_eq!(token["id"], "eip155:137/slip44:966", "{address}");
+            assert_eq!(token["symbol"], "POL");
+            assert_eq!(token["decimals"], 18);
+            assert_eq!(token["metadata_abi"], "native");
+        }
+
+        let body = call_rpc(
+            services(test_support::migrated_repository(), &list),
+            "eth_getTokensMetadata",
+            json!({ "tokens": [
+                { "chain_id": 137, "address": "slip44:60" },
+                { "chain_id": 2, "address": "slip44:60" },
+                { "chain_id": 137, "address": "0xEeeeeEeeeEeEeeEeEeEeeEEEeeeeEeeeeeeeEEeE" },
+            ]}),
+        )
+        .await;
+        let items = body["result"].as_array().unwrap();
+        assert_eq!(items[0]["error"]["code"], rpc_error::INVALID_PARAMS);
+        assert_eq!(items[1]["error"]["code"], rpc_error::UNKNOWN_CHAIN);
+        assert_eq!(items[2]["token"]["symbol"], "POL");
+    }
+
+    #[actix_web::test]
+    async fn batch_reports_error_codes_per_item() {
+        let list = chainlist_mock(42, "https://rpc.example/").await;
+
+        let body = call_rpc(
+            services(test_support::migrated_repository(), &list),
+            "eth_getTokensMetadata",
+            json!({ "tokens": [
+                { "chain_id": 999, "address": Address::repeat_byte(1).to_string() },
+                { "chain_id": 42, "address": "0x123" },
+            ]}),
+        )
+        .await;
+
+        let items = body["result"].as_array().expect("batch result");
+        assert_eq!(items.len(), 2);
+        assert_eq!(items[0]["error"]["code"], rpc_error::UNKNOWN_CHAIN);
+        assert_eq!(items[1]["error"]["code"], rpc_error::INVALID_PARAMS);
+    }
+
+    #[actix_web::test]
+    async fn batch_resolves_chains_concurrently() {
+        // Every request to either chain's RPC takes a while.
+        let mut rpcs = Vec::new();
+        for chain_id in [1u64, 2] {
+            let rpc = MockServer::start().await;
+            Mock::given(method("POST"))
+                .respond_with(move |req: &Request| {
+                    let id = serde_json::from_slice::<Value>(&req.body)
+                        .ok()
+                        .and_then(|v| v.get("id").cloned())
+                        .unwrap_or(json!(0));
+                    ResponseTemplate::new(200)
+                        .set_body_json(json!({
+                            "jsonrpc": "2.0",
+                            "id": id,
+                            "result": format!("0x{chain_id:x}"),
+                        }))
+                        .set_delay(Duration::from_millis(200))
+                })
+                .mount(&rpc)
+                .await;
+            rpcs.push(rpc);
+        }
+        let list = MockServer::start().await;
+        Mock::given(method("GET"))
+            .and(path("/rpcs.json"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(json!([
+                { "name": "A", "chain": "A", "chainId": 1, "rpc": [{ "url": rpcs[0].uri() }] },
+                { "name": "B", "chain": "B", "chainId": 2, "rpc": [{ "url": rpcs[1].uri() }] },
+            ])))
+            .mount(&list)
+            .await;
+
+        let timed = async |chains: &[ChainId]| {
+            let tokens: Vec<Value> = chains
+                .iter()
+                .map(|chain_id| json!({ "chain_id": chain_id, "address": Address::repeat_byte(1).to_string() }))
+                .collect();
+            let started = std::time::Instant::now();
+            let body = call_rpc(
+                services(test_support::migrated_repository(), &list),
+                "eth_getTokensMetadata",
+                json!({ "tokens": tokens }),
+            )
+            .await;
+            assert_eq!(body["result"].as_array().unwrap().len(), chains.len());
+            started.elapsed()
+        };
+
+        let one = timed(&[1]).await;
+        let both = timed(&[1, 2]).await;
+        assert!(
+            both < one * 3 / 2,
+            "one chain took {one:?}, two took {both:?}"
+        );
+    }
+}
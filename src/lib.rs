@@ -5,4 +5,7 @@ pub mod token;
 pub mod types;
 
 pub mod chainlist;
+pub mod config;
 pub mod handlers;
+pub mod native;
+pub mod rpc_error;
@@ -1,21 +1,24 @@
-use std::{env, time::Duration};
+use std::{env, sync::Arc};
 
 use actix_cors::Cors;
 use actix_web::{App, HttpServer, web::Data};
 use dotenv::dotenv;
-use jsonrpc_v2::Server;
-use log::info;
+use log::{error, info};
 
 use token_api::{
-    handlers::{get_evm_token_metadata, get_evm_token_metadata_with_rpc_url, hello_world},
-    repositories::sqlite::evm_token::SqliteEvmTokenRepository,
-    services::{chainlist::ChainlistService, evm::EvmTokenService, provider::ProviderService},
+    config::{self, Config},
+    handlers::{hello_world, rpc_server},
+    repositories::{
+        MigrationMode, cache::CachedEvmTokenRepository, evm_token_repository_from_url,
+        redis::evm_token::RedisCachedEvmTokenRepository,
+    },
+    services::{
+        chainlist::ChainlistService,
+        evm::{EvmTokenService, RefreshPolicy},
+        provider::ProviderService,
+    },
 };
 
-const CHAINLIST_TTL: Duration = Duration::from_hours(24);
-/// How long to reuse the same Fallback [`RpcClient`] (keeps Alloy transport rankings; refresh picks up new Chainlist URLs).
-const PROVIDER_CACHE_TTL: Duration = Duration::from_secs(15 * 60);
-
 #[actix_web::main]
 async fn main() -> std::io::Result<()> {
     env_logger::init();
@@ -23,53 +26,101 @@ async fn main() -> std::io::Result<()> {
 
     info!("Hello, world full of tokens!");
 
-    let port = env::var("PORT")
-        .expect("PORT must be set")
-        .parse::<u16>()
-        .expect("PORT must be a number");
-
-    let host = env::var("HOST").expect("HOST must be set");
-
-    let workers = env::var("WORKERS")
-        .expect("WORKERS must be set")
-        .parse::<usize>()
-        .expect("WORKERS must be a number");
-
-    let database_url = env::var("DATABASE_URL").expect("DATABASE_URL must be set");
-
-    let dev_cors = matches!(
-        env::var("APP_ENV").as_deref(),
-        Ok("development") | Ok("dev")
+    let startup = Startup::from_args(env::args().skip(1)).unwrap_or_else(|arg| {
+        error!("Unexpected argument {arg}; usage: token-api [--migrate-only | --no-migrate]");
+        std::process::exit(2);
+    });
+
+    let config = Config::load().unwrap_or_else(|e| {
+        error!("Invalid configuration: {e}");
+        std::process::exit(1);
+    });
+    info!(
+        "Using {:?} repository, CORS {:?}",
+        config.repository_backend(),
+        config.cors
     );
-    if dev_cors {
-        info!("APP_ENV=development: permissive CORS enabled");
+
+    let mut evm_token_repository = evm_token_repository_from_url(config.database_url.clone())
+        .expect("DATABASE_URL must be a valid database");
+    let migration_mode = match startup {
+        Startup::NoMigrate => MigrationMode::VerifyOnly,
+        Startup::Serve | Startup::MigrateOnly => MigrationMode::Apply,
+    };
+    evm_token_repository
+        .migrate(migration_mode)
+        .await
+        .map_err(std::io::Error::other)?;
+    if startup == Startup::MigrateOnly {
+        info!("Migrations applied, exiting (--migrate-only)");
+        return Ok(());
     }
 
-    let evm_token_repository = SqliteEvmTokenRepository::new(database_url);
+    let refresh_policy = RefreshPolicy {
+        interval: config.token_refresh_interval,
+        max_age: config.token_max_age,
+        batch_size: config.token_refresh_batch_size,
+        retry_after: config.token_refresh_retry,
+    };
+
+    if let Some(redis) = &config.redis {
+        info!("Caching tokens in Redis under {}:", redis.key_prefix);
+        evm_token_repository = Arc::new(
+            RedisCachedEvmTokenRepository::connect(
+                evm_token_repository,
+                &redis.url,
+                redis.key_prefix.clone(),
+                redis.ttl,
+            )
+            .await
+            .expect("REDIS_URL must point to a reachable Redis"),
+        );
+    }
+    if let Some(capacity) = config.token_cache_capacity {
+        info!("Caching up to {capacity} tokens in memory");
+        // Entries live one refresh interval, so other replicas' refreshes show up by the next run.
+        evm_token_repository = Arc::new(CachedEvmTokenRepository::new(
+            evm_token_repository,
+            capacity,
+            config.token_refresh_interval,
+        ));
+    }
 
-    let evm_token_service = EvmTokenService::new(evm_token_repository);
+    let evm_token_service =
+        EvmTokenService::new(evm_token_repository.clone(), config.negative_cache_ttl);
+
+    let mut chainlist_service = ChainlistService::with_client_and_sources(
+        config.chainlist_ttl,
+        reqwest::Client::new(),
+        config.chainlist_sources.clone(),
+    )
+    .with_rpc_secrets(config.rpc_secrets.clone())
+    .with_rpc_policy(config.rpc_policy)
+    .with_bundled_snapshot(config.chainlist_bundled_snapshot);
+    if let Some(path) = &config.chainlist_snapshot_path {
+        chainlist_service = chainlist_service.with_snapshot_path(path);
+    }
+    let provider_service =
+        ProviderService::new(chainlist_service.clone(), config.provider_cache_ttl)
+            .with_fallback_active_cap(config.fallback_active_cap)
+            .with_rpc_overrides(config.rpc_overrides.clone());
 
-    let chainlist_service = ChainlistService::new(CHAINLIST_TTL);
-    let provider_service = ProviderService::new(chainlist_service.clone(), PROVIDER_CACHE_TTL);
+    evm_token_service.spawn_refresh(provider_service.clone(), refresh_policy);
 
-    let rpc = Server::new()
-        .with_data(jsonrpc_v2::Data::new(evm_token_service.clone()))
-        .with_data(jsonrpc_v2::Data::new(provider_service.clone()))
-        .with_method(
-            "eth_getTokenMetadataWithRpc",
-            get_evm_token_metadata_with_rpc_url,
-        )
-        .with_method("eth_getTokenMetadata", get_evm_token_metadata)
-        .finish();
+    let rpc = rpc_server(evm_token_service.clone(), provider_service.clone());
 
-    info!("Starting server on port {}", port);
+    info!("Starting server on port {}", config.port);
 
+    let cors_config = config.cors.clone();
     HttpServer::new(move || {
         let rpc = rpc.clone();
-        let cors = if dev_cors {
-            Cors::permissive()
-        } else {
-            Cors::default()
+        let cors = match &cors_config {
+            config::Cors::Permissive => Cors::permissive(),
+            config::Cors::Origins(origins) => origins
+                .iter()
+                .fold(Cors::default(), |cors, origin| cors.allowed_origin(origin))
+                .allowed_methods(["GET", "POST"])
+                .allowed_header(actix_web::http::header::CONTENT_TYPE),
         };
         App::new()
             .wrap(cors)
@@ -81,8 +132,40 @@ async fn main() -> std::io::Result<()> {
                     .finish(rpc.into_web_service()),
             )
     })
-    .bind((host, port))?
-    .workers(workers)
+    .bind((config.host.as_str(), config.port))?
+    .workers(config.workers)
     .run()
-    .await
+    .await?;
+
+    info!("Server stopped, shutting down repository");
+    evm_token_repository
+        .shutdown()
+        .await
+        .map_err(std::io::Error::other)
+}
+
+/// What the binary does on start, from its command line.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Startup {
+    /// Apply pending migrations, then serve.
+    Serve,
+    /// `--migrate-only`: apply pending migrations and exit.
+    MigrateOnly,
+    /// `--no-migrate`: serve without applying migrations (the schema is still checked).
+    NoMigrate,
+}
+
+impl Startup {
+    /// The mode `args` select; the first unknown or repeated argument otherwise.
+    fn from_args(args: impl IntoIterator<Item = String>) -> Result<Self, String> {
+        let mut startup = Startup::Serve;
+        for arg in args {
+            startup = match arg.as_str() {
+                "--migrate-only" if startup == Startup::Serve => Startup::MigrateOnly,
+                "--no-migrate" if startup == Startup::Serve => Startup::NoMigrate,
+                _ => return Err(arg),
+            };
+        }
+        Ok(startup)
+    }
 }
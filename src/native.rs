@@ -0,0 +1,76 @@
+//! A chain's native currency (ETH, MATIC, BNB, ...) as a [`Token`], built from Chainlist's
+//! `nativeCurrency` rather than read from a contract.
+
+use alloy::primitives::{Address, address};
+use tap_caip::ChainId as CaipChainId;
+
+use crate::{
+    chainlist::Chain,
+    token::{MetadataAbi, SLIP44_NAMESPACE, Token, TokenId},
+};
+
+/// Placeholder address wallets and DEX aggregators use for the native currency; looking it up
+/// returns the native token.
+pub const NATIVE_TOKEN_ADDRESS: Address = address!("0xEeeeeEeeeEeEeeEeEeEeeEEEeeeeEeeeeeeeEEeE");
+
+/// SLIP-44 coin type of chains Chainlist gives none for: Ether's, which most EVM chains use.
+pub const DEFAULT_SLIP44: u32 = 60;
+
+/// SLIP-44 coin type of `chain`'s native currency.
+pub fn slip44(chain: &Chain) -> u32 {
+    chain.slip44.unwrap_or(DEFAULT_SLIP44)
+}
+
+/// `chain`'s native currency as a token with a `slip44` id, e.g. `eip155:1/slip44:60`; `None` if
+/// Chainlist has no `nativeCurrency` for it.
+pub fn native_token(chain: &Chain) -> Option<Token> {
+    let currency = chain.native_currency.as_ref()?;
+    let caip_chain_id = CaipChainId::new("eip155", &chain.chain_id.to_string()).ok()?;
+    let id = TokenId::new(caip_chain_id, SLIP44_NAMESPACE, &slip44(chain).to_string()).ok()?;
+    Some(Token::new(
+        id,
+        Some(currency.name.clone()),
+        Some(currency.symbol.clone()),
+        currency.decimals,
+        MetadataAbi::Native,
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{chainlist::NativeCurrency, types::ChainId};
+
+    fn chain(chain_id: ChainId, slip44: Option<u32>) -> Chain {
+        Chain {
+            name: "Polygon Mainnet".to_string(),
+            chain: "Polygon".to_string(),
+            chain_id,
+            short_name: None,
+            slip44,
+            native_currency: Some(NativeCurrency {
+                name: "POL".to_string(),
+                symbol: "POL".to_string(),
+                decimals: 18,
+            }),
+            explorers: vec![],
+            rpc: vec![],
+        }
+    }
+
+    #[test]
+    fn native_token_comes_from_chainlist_currency() {
+        let token = native_token(&chain(137, Some(966))).unwrap();
+        assert_eq!(token.id.to_string(), "eip155:137/slip44:966");
+        assert_eq!(token.symbol.as_deref(), Some("POL"));
+        assert_eq!(token.decimals, 18);
+        assert_eq!(token.metadata_abi, MetadataAbi::Native);
+
+        let without_slip44 = native_token(&chain(8453, None)).unwrap();
+        assert_eq!(without_slip44.id.to_string(), "eip155:8453/slip44:60");
+
+        let mut no_currency = chain(1, None);
+        no_currency.native_currency = None;
+        assert!(native_token(&no_currency).is_none());
+    }
+}
@@ -0,0 +1,290 @@
+//! Read-through LRU cache of tokens in front of any [`EvmTokenRepository`], so hot tokens are served
+//! without a database round trip. Negative results and the freshness query go straight through.
+//! Entries expire after a TTL, so updates written by other replicas (e.g. by their background
+//! refresh) are picked up.
+
+use std::{
+    num::NonZeroUsize,
+    sync::{
+        Mutex,
+        atomic::{AtomicU64, Ordering},
+    },
+    time::{Duration, Instant},
+};
+
+use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+use log::info;
+use lru::LruCache;
+
+use crate::{
+    repositories::{
+        DynEvmTokenRepository, EvmTokenRepository, MigrationMode, RepoError, Repository,
+    },
+    token::{NegativeTokenResult, Token, TokenId},
+};
+
+pub struct CachedEvmTokenRepository {
+    inner: DynEvmTokenRepository,
+    /// Tokens by `TokenId` string.
+    tokens: Mutex<LruCache<String, CachedToken>>,
+    ttl: Duration,
+    hits: AtomicU64,
+    misses: AtomicU64,
+}
+
+struct CachedToken {
+    token: Token,
+    inserted: Instant,
+}
+
+/// Token lookups answered from the cache (`hits`) or passed to the wrapped repository (`misses`).
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct CacheStats {
+    pub hits: u64,
+    pub misses: u64,
+}
+
+impl CachedEvmTokenRepository {
+    /// Caches up to `capacity` tokens of `inner` for `ttl` each, evicting the least recently used.
+    pub fn new(inner: DynEvmTokenRepository, capacity: NonZeroUsize, ttl: Duration) -> Self {
+        Self {
+            inner,
+            tokens: Mutex::new(LruCache::new(capacity)),
+            ttl,
+            hits: AtomicU64::new(0),
+            misses: AtomicU64::new(0),
+        }
+    }
+
+    pub fn stats(&self) -> CacheStats {
+        CacheStats {
+            hits: self.hits.load(Ordering::Relaxed),
+            misses: self.misses.load(Ordering::Relaxed),
+        }
+    }
+
+    fn cached(&self, key: &str) -> Option<Token> {
+        let mut tokens = self.tokens.lock().expect("token cache lock poisoned");
+        self.live(&mut tokens, key)
+            .map(|cached| cached.token.clone())
+    }
+
+    /// The entry under `key` if it has not expired; an expired one is dropped.
+    fn live<'a>(
+        &self,
+        tokens: &'a mut LruCache<String, CachedToken>,
+        key: &str,
+    ) -> Option<&'a CachedToken> {
+        if tokens
+            .peek(key)
+            .is_some_and(|cached| cached.inserted.elapsed() >= self.ttl)
+        {
+            tokens.pop(key);
+        }
+        tokens.get(key)
+    }
+
+    fn insert(&self, token: &Token) {
+        self.tokens.lock().expect("token cache lock poisoned").put(
+            token.id.to_string(),
+            CachedToken {
+                token: token.clone(),
+                inserted: Instant::now(),
+            },
+        );
+    }
+}
+
+#[async_trait]
+impl Repository<Token> for CachedEvmTokenRepository {
+    async fn get(&self, id: TokenId) -> Result<Option<Token>, RepoError> {
+        if let Some(token) = self.cached(&id.to_string()) {
+            self.hits.fetch_add(1, Ordering::Relaxed);
+            return Ok(Some(token));
+        }
+
+        self.misses.fetch_add(1, Ordering::Relaxed);
+        let token = Repository::<Token>::get(self.inner.as_ref(), id).await?;
+        if let Some(token) = &token {
+            self.insert(token);
+        }
+        Ok(token)
+    }
+
+    async fn save(&self, token: &Token) -> Result<(), RepoError> {
+        Repository::<Token>::save(self.inner.as_ref(), token).await?;
+
+        // `save` keeps an existing row, which may differ from `token`: cache what was stored.
+        if let Some(stored) =
+            Repository::<Token>::get(self.inner.as_ref(), token.id.clone()).await?
+        {
+            self.insert(&stored);
+        }
+        Ok(())
+    }
+
+    async fn update(&self, token: &Token) -> Result<(), RepoError> {
+        Repository::<Token>::update(self.inner.as_ref(), token).await?;
+        self.insert(token);
+        Ok(())
+    }
+}
+
+#[async_trait]
+impl Repository<NegativeTokenResult> for CachedEvmTokenRepository {
+    async fn get(&self, id: TokenId) -> Result<Option<NegativeTokenResult>, RepoError> {
+        Repository::<NegativeTokenResult>::get(self.inner.as_ref(), id).await
+    }
+
+    async fn save(&self, result: &NegativeTokenResult) -> Result<(), RepoError> {
+        self.inner.save(result).await
+    }
+
+    async fn update(&self, result: &NegativeTokenResult) -> Result<(), RepoError> {
+        self.inner.update(result).await
+    }
+}
+
+#[async_trait]
+impl EvmTokenRepository for CachedEvmTokenRepository {
+    async fn claim_stale(
+        &self,
+        cutoff: DateTime<Utc>,
+        retry_cutoff: DateTime<Utc>,
+        attempted_at: DateTime<Utc>,
+        limit: usize,
+    ) -> Result<Vec<Token>, RepoError> {
+        self.inner
+            .claim_stale(cutoff, retry_cutoff, attempted_at, limit)
+            .await
+    }
+
+    async fn migrate(&self, mode: MigrationMode) -> Result<(), RepoError> {
+        self.inner.migrate(mode).await
+    }
+
+    async fn shutdown(&self) -> Result<(), RepoError> {
+        let stats = self.stats();
+        info!("Token cache: {} hits, {} misses", stats.hits, stats.misses);
+        self.inner.shutdown().await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+
+    use super::*;
+    use crate::{
+        repositories::{
+            conformance::repository_conformance_tests,
+            memory::evm_token::InMemoryEvmTokenRepository,
+        },
+        token::MetadataAbi,
+    };
+
+    fn cached(capacity: usize) -> (Arc<InMemoryEvmTokenRepository>, CachedEvmTokenRepository) {
+        cached_for(capacity, Duration::from_secs(3600))
+    }
+
+    fn cached_for(
+        capacity: usize,
+        ttl: Duration,
+    ) -> (Arc<InMemoryEvmTokenRepository>, CachedEvmTokenRepository) {
+        let inner = Arc::new(InMemoryEvmTokenRepository::default());
+        let repository =
+            CachedEvmTokenRepository::new(inner.clone(), NonZeroUsize::new(capacity).unwrap(), ttl);
+        (inner, repository)
+    }
+
+    repository_conformance_tests!(cached(100).1);
+
+    fn token(address_byte: u8, symbol: &str) -> Token {
+        let id = format!(
+            "eip155:1/erc20:0x{}",
+            format!("{address_byte:02x}").repeat(20)
+        )
+        .parse()
+        .unwrap();
+        Token::new(
+            id,
+            Some("Token".to_string()),
+            Some(symbol.to_string()),
+            18,
+            MetadataAbi::String,
+        )
+    }
+
+    #[tokio::test]
+    async fn reads_populate_the_cache_and_count_hits_and_misses() {
+        let (inner, repository) = cached(100);
+        let stored = token(0x0a, "TKN");
+        inner.save(&stored).await.unwrap();
+
+        for _ in 0..3 {
+            let token: Option<Token> = Repository::<Token>::get(&repository, stored.id.clone())
+                .await
+                .unwrap();
+            assert!(token.is_some());
+        }
+        let absent: Option<Token> = Repository::<Token>::get(&repository, token(0x0b, "X").id)
+            .await
+            .unwrap();
+        assert!(absent.is_none());
+
+        assert_eq!(repository.stats(), CacheStats { hits: 2, misses: 2 });
+    }
+
+    #[tokio::test]
+    async fn save_and_update_are_served_from_the_cache_until_the_ttl_passes() {
+        let (inner, repository) = cached_for(100, Duration::from_millis(100));
+        let saved = token(0x0a, "OLD");
+        repository.save(&saved).await.unwrap();
+        let get = || async {
+            let token: Token = Repository::<Token>::get(&repository, saved.id.clone())
+                .await
+                .unwrap()
+                .unwrap();
+            token.symbol
+        };
+
+        // Changed behind the cache's back, e.g. by another replica: reads keep serving the cached
+        // record until it expires.
+        inner.update(&token(0x0a, "INNER")).await.unwrap();
+        assert_eq!(get().await.as_deref(), Some("OLD"));
+        tokio::time::sleep(Duration::from_millis(150)).await;
+        assert_eq!(get().await.as_deref(), Some("INNER"));
+
+        repository.update(&token(0x0a, "NEW")).await.unwrap();
+        assert_eq!(get().await.as_deref(), Some("NEW"));
+        assert_eq!(repository.stats(), CacheStats { hits: 2, misses: 1 });
+    }
+
+    #[tokio::test]
+    async fn saving_over_an_existing_row_caches_the_stored_token() {
+        let (inner, repository) = cached(100);
+        inner.save(&token(0x0a, "STORED")).await.unwrap();
+
+        repository.save(&token(0x0a, "CALLER")).await.unwrap();
+        let cached: Token = Repository::<Token>::get(&repository, token(0x0a, "X").id)
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(cached.symbol.as_deref(), Some("STORED"));
+        assert_eq!(repository.stats(), CacheStats { hits: 1, misses: 0 });
+    }
+
+    #[tokio::test]
+    async fn capacity_evicts_least_recently_used_token() {
+        let (_, repository) = cached(1);
+        let (a, b) = (token(0x0a, "A"), token(0x0b, "B"));
+        repository.save(&a).await.unwrap();
+        repository.save(&b).await.unwrap();
+
+        let _: Option<Token> = Repository::<Token>::get(&repository, a.id.clone())
+            .await
+            .unwrap();
+        assert_eq!(repository.stats(), CacheStats { hits: 0, misses: 1 });
+    }
+}
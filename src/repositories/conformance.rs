@@ -0,0 +1,237 @@
+//! Behaviour every [`EvmTokenRepository`] backend must share. Each backend instantiates the suite
+//! with [`repository_conformance_tests!`] and a factory for an empty, migrated repository.
+//! Timestamps are compared at second precision, the coarsest any backend stores.
+
+use chrono::{DateTime, TimeDelta, Utc};
+
+use super::{EvmTokenRepository, RepoError, Repository};
+use crate::token::{MetadataAbi, NegativeReason, NegativeTokenResult, Token, TokenId};
+
+fn token_id(address_byte: u8) -> TokenId {
+    format!(
+        "eip155:1/erc20:0x{}",
+        format!("{address_byte:02x}").repeat(20)
+    )
+    .parse()
+    .expect("valid account id")
+}
+
+fn token(address_byte: u8, symbol: &str) -> Token {
+    Token::new(
+        token_id(address_byte),
+        Some("Dead".to_string()),
+        Some(symbol.to_string()),
+        18,
+        MetadataAbi::String,
+    )
+}
+
+fn at(seconds: i64) -> DateTime<Utc> {
+    DateTime::from_timestamp(seconds, 0).unwrap()
+}
+
+pub(crate) async fn token_round_trips_with_freshness(repository: &dyn EvmTokenRepository) {
+    let mut saved = token(0xde, "DEAD").with_source_block(Some(19_000_000));
+    saved.fetched_at = at(1_700_000_000);
+    saved.last_verified_at = at(1_700_000_100);
+    repository.save(&saved).await.unwrap();
+
+    let stored: Token = Repository::<Token>::get(repository, token_id(0xde))
+        .await
+        .unwrap()
+        .expect("token stored");
+    assert_eq!(stored.id, saved.id);
+    assert_eq!(stored.name.as_deref(), Some("Dead"));
+    assert_eq!(stored.symbol.as_deref(), Some("DEAD"));
+    assert_eq!(stored.decimals, 18);
+    assert_eq!(stored.metadata_abi, MetadataAbi::String);
+    assert_eq!(stored.fetched_at, saved.fetched_at);
+    assert_eq!(stored.last_verified_at, saved.last_verified_at);
+    assert_eq!(stored.source_block, Some(19_000_000));
+
+    let missing: Option<Token> = Repository::<Token>::get(repository, token_id(0xad))
+        .await
+        .unwrap();
+    assert!(missing.is_none());
+}
+
+pub(crate) async fn saving_an_existing_token_is_a_no_op(repository: &dyn EvmTokenRepository) {
+    let mut first = token(0xde, "OLD").with_source_block(Some(19_000_000));
+    first.fetched_at = at(1_700_000_000);
+    first.last_verified_at = at(1_700_000_100);
+    repository.save(&first).await.unwrap();
+
+    let mut second = Token::new(token_id(0xde), None, None, 6, MetadataAbi::Bytes32)
+        .with_source_block(Some(20_000_000));
+    second.fetched_at = at(1_800_000_000);
+    second.last_verified_at = at(1_800_000_100);
+    repository.save(&second).await.unwrap();
+
+    let stored: Token = Repository::<Token>::get(repository, token_id(0xde))
+        .await
+        .unwrap()
+        .expect("token stored");
+    assert_eq!(stored.name.as_deref(), Some("Dead"));
+    assert_eq!(stored.symbol.as_deref(), Some("OLD"));
+    assert_eq!(stored.decimals, 18);
+    assert_eq!(stored.metadata_abi, MetadataAbi::String);
+    assert!(stored.missing_fields.is_empty());
+    assert_eq!(stored.fetched_at, first.fetched_at);
+    assert_eq!(stored.last_verified_at, first.last_verified_at);
+    assert_eq!(stored.source_block, Some(19_000_000));
+}
+
+pub(crate) async fn update_overwrites_metadata_including_missing_fields(
+    repository: &dyn EvmTokenRepository,
+) {
+    repository.save(&token(0xde, "OLD")).await.unwrap();
+
+    let upgraded = Token::new(
+        token_id(0xde),
+        None,
+        Some("NEW".to_string()),
+        6,
+        MetadataAbi::Bytes32,
+    );
+    repository.update(&upgraded).await.unwrap();
+
+    let stored: Token = Repository::<Token>::get(repository, token_id(0xde))
+        .await
+        .unwrap()
+        .expect("token stored");
+    assert_eq!(stored.name, None);
+    assert_eq!(stored.symbol.as_deref(), Some("NEW"));
+    assert_eq!(stored.decimals, 6);
+    assert_eq!(stored.metadata_abi, MetadataAbi::Bytes32);
+}
+
+pub(crate) async fn update_of_unknown_token_is_not_found(repository: &dyn EvmTokenRepository) {
+    let err = repository
+        .update(&token(0xde, "NEW"))
+        .await
+        .expect_err("nothing to update");
+    assert!(matches!(err, RepoError::NotFound));
+}
+
+pub(crate) async fn negative_result_round_trips_and_update_overwrites(
+    repository: &dyn EvmTokenRepository,
+) {
+    let first = NegativeTokenResult {
+        id: token_id(0xde),
+        reason: NegativeReason::NotAContract,
+        message: "no code".to_string(),
+        recorded_at: at(1_700_000_000),
+    };
+    repository.save(&first).await.unwrap();
+
+    let second = NegativeTokenResult {
+        reason: NegativeReason::InvalidMetadata,
+        message: "decimals reverted".to_string(),
+        recorded_at: at(1_700_000_500),
+        ..first.clone()
+    };
+    repository.save(&second).await.unwrap();
+
+    let stored: NegativeTokenResult =
+        Repository::<NegativeTokenResult>::get(repository, token_id(0xde))
+            .await
+            .unwrap()
+            .expect("negative result stored");
+    assert_eq!(stored.reason, NegativeReason::NotAContract);
+
+    repository.update(&second).await.unwrap();
+
+    let stored: NegativeTokenResult =
+        Repository::<NegativeTokenResult>::get(repository, token_id(0xde))
+            .await
+            .unwrap()
+            .expect("negative result stored");
+    assert_eq!(stored.reason, NegativeReason::InvalidMetadata);
+    assert_eq!(stored.message, "decimals reverted");
+    assert_eq!(stored.recorded_at, second.recorded_at);
+}
+
+pub(crate) async fn claim_stale_returns_oldest_first(repository: &dyn EvmTokenRepository) {
+    let now = Utc::now();
+    for (address_byte, age_days) in [(0x01, 3), (0x02, 0), (0x03, 10), (0x04, 5)] {
+        let mut token = token(address_byte, "TKN");
+        token.last_verified_at = now - TimeDelta::days(age_days);
+        repository.save(&token).await.unwrap();
+    }
+
+    let stale = repository
+        .claim_stale(now - TimeDelta::days(1), now, now, 2)
+        .await
+        .unwrap();
+    let ids: Vec<TokenId> = stale.into_iter().map(|token| token.id).collect();
+    assert_eq!(ids, vec![token_id(0x03), token_id(0x04)]);
+}
+
+pub(crate) async fn claim_stale_skips_tokens_attempted_since_retry_cutoff(
+    repository: &dyn EvmTokenRepository,
+) {
+    let now = Utc::now();
+    for (address_byte, age_days) in [(0x01, 10), (0x02, 5), (0x03, 3)] {
+        let mut token = token(address_byte, "TKN");
+        token.last_verified_at = now - TimeDelta::days(age_days);
+        repository.save(&token).await.unwrap();
+    }
+    let claim = |retry_cutoff, attempted_at| async move {
+        repository
+            .claim_stale(now - TimeDelta::days(1), retry_cutoff, attempted_at, 2)
+            .await
+            .unwrap()
+            .into_iter()
+            .map(|token| token.id)
+            .collect::<Vec<TokenId>>()
+    };
+
+    let retry_cutoff = now - TimeDelta::hours(1);
+    assert_eq!(
+        claim(retry_cutoff, now).await,
+        vec![token_id(0x01), token_id(0x02)]
+    );
+    assert_eq!(claim(retry_cutoff, now).await, vec![token_id(0x03)]);
+    assert_eq!(claim(retry_cutoff, now).await, Vec::<TokenId>::new());
+
+    // Storing a verified copy clears the attempt; the rest are due again after the retry delay.
+    let mut verified = token(0x02, "TKN");
+    verified.last_verified_at = now - TimeDelta::days(2);
+    repository.update(&verified).await.unwrap();
+    assert_eq!(claim(retry_cutoff, now).await, vec![token_id(0x02)]);
+    let later = now + TimeDelta::hours(2);
+    assert_eq!(
+        claim(later - TimeDelta::hours(1), later).await,
+        vec![token_id(0x01), token_id(0x03)]
+    );
+}
+
+/// Instantiates the conformance suite as `#[tokio::test]`s in the calling module. `$repository` is
+/// an expression evaluating to a fresh, migrated repository; extra attributes (e.g. `#[ignore]`)
+/// apply to every test.
+macro_rules! repository_conformance_tests {
+    ($repository:expr $(, #[$meta:meta])* $(,)?) => {
+        repository_conformance_tests!(@tests $repository, [$(#[$meta])*],
+            token_round_trips_with_freshness,
+            saving_an_existing_token_is_a_no_op,
+            update_overwrites_metadata_including_missing_fields,
+            update_of_unknown_token_is_not_found,
+            negative_result_round_trips_and_update_overwrites,
+            claim_stale_returns_oldest_first,
+            claim_stale_skips_tokens_attempted_since_retry_cutoff,
+        );
+    };
+    (@tests $repository:expr, $attrs:tt, $($name:ident),* $(,)?) => {
+        $(repository_conformance_tests!(@test $repository, $attrs, $name);)*
+    };
+    (@test $repository:expr, [$(#[$meta:meta])*], $name:ident) => {
+        #[tokio::test]
+        $(#[$meta])*
+        async fn $name() {
+            let repository = $repository;
+            $crate::repositories::conformance::$name(&repository).await;
+        }
+    };
+}
+
+pub(crate) use repository_conformance_tests;
@@ -0,0 +1,424 @@
+//! Tokens held in process memory: nothing to migrate, an optional LRU bound, and an optional JSON
+//! snapshot loaded at startup and written on shutdown. Meant for tests, CI and preview deployments.
+//! Without a bound entries live in a sharded concurrent map, so lookups do not queue behind one lock.
+
+use std::{
+    collections::HashMap,
+    fs,
+    num::NonZeroUsize,
+    path::{Path, PathBuf},
+    sync::{Arc, Mutex},
+};
+
+use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+use dashmap::DashMap;
+use log::{debug, info};
+use lru::LruCache;
+use serde::{Deserialize, Serialize};
+use url::Url;
+
+use crate::{
+    repositories::{EvmTokenRepository, RepoError, Repository},
+    token::{NegativeTokenResult, Token, TokenId},
+};
+
+#[derive(Clone)]
+pub struct InMemoryEvmTokenRepository {
+    tokens: Arc<Store<Token>>,
+    negative_results: Arc<Store<NegativeTokenResult>>,
+    /// Refresh attempts since each token was last stored; not part of the snapshot.
+    refresh_attempts: Arc<Mutex<HashMap<TokenId, DateTime<Utc>>>>,
+    snapshot_path: Option<PathBuf>,
+}
+
+/// On-disk snapshot; with a capacity, entries are ordered least to most recently used.
+#[derive(Default, Serialize, Deserialize)]
+struct Snapshot {
+    tokens: Vec<Token>,
+    negative_results: Vec<NegativeTokenResult>,
+}
+
+impl Default for InMemoryEvmTokenRepository {
+    fn default() -> Self {
+        Self::new(None)
+    }
+}
+
+impl InMemoryEvmTokenRepository {
+    /// Empty repository; with a `capacity`, each of tokens and negative results keeps at most that
+    /// many entries, evicting the least recently used.
+    pub fn new(capacity: Option<NonZeroUsize>) -> Self {
+        Self {
+            tokens: Arc::new(Store::new(capacity)),
+            negative_results: Arc::new(Store::new(capacity)),
+            refresh_attempts: Arc::default(),
+            snapshot_path: None,
+        }
+    }
+
+    /// Repository persisted to `path`: loaded from it if the file exists, written back by
+    /// [`EvmTokenRepository::shutdown`].
+    pub fn with_snapshot(
+        capacity: Option<NonZeroUsize>,
+        path: impl Into<PathBuf>,
+    ) -> Result<Self, RepoError> {
+        let path = path.into();
+        let mut repository = Self::new(capacity);
+
+        if path.exists() {
+            let snapshot: Snapshot = serde_json::from_slice(&fs::read(&path).map_err(|e| {
+                RepoError::Backend(format!("Failed to read snapshot {}: {e}", path.display()))
+            })?)
+            .map_err(|e| RepoError::Backend(format!("Invalid snapshot {}: {e}", path.display())))?;
+
+            info!(
+                "Loaded {} tokens and {} negative results from {}",
+                snapshot.tokens.len(),
+                snapshot.negative_results.len(),
+                path.display()
+            );
+            repository.restore(snapshot);
+        }
+
+        repository.snapshot_path = Some(path);
+        Ok(repository)
+    }
+
+    /// Repository for a `memory:` URL. Query parameters: `capacity` (LRU bound per table) and
+    /// `snapshot` (file path), e.g. `memory:?capacity=10000&snapshot=/data/tokens.json`.
+    pub fn from_url(database_url: &str) -> Result<Self, RepoError> {
+        let url = Url::parse(database_url)
+            .map_err(|e| RepoError::Backend(format!("Invalid memory URL: {e}")))?;
+
+        let mut capacity = None;
+        let mut snapshot = None;
+        for (key, value) in url.query_pairs() {
+            match key.as_ref() {
+                "capacity" => {
+                    capacity = Some(value.parse::<NonZeroUsize>().map_err(|e| {
+                        RepoError::Backend(format!("Invalid memory capacity {value}: {e}"))
+                    })?)
+                }
+                "snapshot" => snapshot = Some(PathBuf::from(value.as_ref())),
+                other => {
+                    return Err(RepoError::Backend(format!(
+                        "Unknown memory URL parameter: {other}"
+                    )));
+                }
+            }
+        }
+
+        match snapshot {
+            Some(path) => Self::with_snapshot(capacity, path),
+            None => Ok(Self::new(capacity)),
+        }
+    }
+
+    /// Writes all entries to `path`, replacing it atomically.
+    pub fn write_snapshot(&self, path: &Path) -> Result<(), RepoError> {
+        let snapshot = Snapshot {
+            tokens: self.tokens.values(),
+            negative_results: self.negative_results.values(),
+        };
+
+        let json = serde_json::to_vec(&snapshot)
+            .map_err(|e| RepoError::Backend(format!("Failed to encode snapshot: {e}")))?;
+        let tmp = path.with_extension("tmp");
+        fs::write(&tmp, json)
+            .and_then(|()| fs::rename(&tmp, path))
+            .map_err(|e| {
+                RepoError::Backend(format!("Failed to write snapshot {}: {e}", path.display()))
+            })?;
+
+        info!(
+            "Wrote {} tokens and {} negative results to {}",
+            snapshot.tokens.len(),
+            snapshot.negative_results.len(),
+            path.display()
+        );
+        Ok(())
+    }
+
+    fn restore(&self, snapshot: Snapshot) {
+        for token in snapshot.tokens {
+            self.tokens.put(token.id.clone(), token);
+        }
+        for result in snapshot.negative_results {
+            self.negative_results.put(result.id.clone(), result);
+        }
+    }
+}
+
+/// One table of the repository.
+enum Store<V> {
+    /// No capacity: a sharded map readers and writers of different ids do not contend on.
+    Concurrent(DashMap<TokenId, V>),
+    /// Every read updates recency, so the LRU sits behind one lock.
+    Lru(Mutex<LruCache<TokenId, V>>),
+}
+
+impl<V: Clone> Store<V> {
+    fn new(capacity: Option<NonZeroUsize>) -> Self {
+        match capacity {
+            Some(capacity) => Store::Lru(Mutex::new(LruCache::new(capacity))),
+            None => Store::Concurrent(DashMap::new()),
+        }
+    }
+
+    fn get(&self, id: &TokenId) -> Option<V> {
+        match self {
+            Store::Concurrent(map) => map.get(id).map(|value| value.clone()),
+            Store::Lru(lru) => lru
+                .lock()
+                .expect("repository lock poisoned")
+                .get(id)
+                .cloned(),
+        }
+    }
+
+    fn contains(&self, id: &TokenId) -> bool {
+        match self {
+            Store::Concurrent(map) => map.contains_key(id),
+            Store::Lru(lru) => lru.lock().expect("repository lock poisoned").contains(id),
+        }
+    }
+
+    /// Stores `value` unless `id` is already stored, as [`Repository::save`].
+    fn save_new(&self, id: &TokenId, value: &V) {
+        match self {
+            Store::Concurrent(map) => {
+                map.entry(id.clone()).or_insert_with(|| value.clone());
+            }
+            Store::Lru(lru) => {
+                let mut lru = lru.lock().expect("repository lock poisoned");
+                if !lru.contains(id) {
+                    lru.put(id.clone(), value.clone());
+                }
+            }
+        }
+    }
+
+    /// Overwrites the value stored under `id`, as [`Repository::update`].
+    fn update_existing(&self, id: &TokenId, value: &V) -> Result<(), RepoError> {
+        let updated = match self {
+            Store::Concurrent(map) => map.get_mut(id).map(|mut stored| *stored = value.clone()),
+            Store::Lru(lru) => lru
+                .lock()
+                .expect("repository lock poisoned")
+                .get_mut(id)
+                .map(|stored| *stored = value.clone()),
+        };
+        updated.ok_or(RepoError::NotFound)
+    }
+
+    fn put(&self, id: TokenId, value: V) {
+        match self {
+            Store::Concurrent(map) => {
+                map.insert(id, value);
+            }
+            Store::Lru(lru) => {
+                lru.lock().expect("repository lock poisoned").put(id, value);
+            }
+        }
+    }
+
+    /// Every stored value; least to most recently used with a capacity.
+    fn values(&self) -> Vec<V> {
+        match self {
+            Store::Concurrent(map) => map.iter().map(|entry| entry.value().clone()).collect(),
+            Store::Lru(lru) => lru
+                .lock()
+                .expect("repository lock poisoned")
+                .iter()
+                .rev()
+                .map(|(_, value)| value.clone())
+                .collect(),
+        }
+    }
+}
+
+#[async_trait]
+impl Repository<Token> for InMemoryEvmTokenRepository {
+    async fn get(&self, id: TokenId) -> Result<Option<Token>, RepoError> {
+        debug!("Finding EVM token by id: {:?}", id.to_string());
+        Ok(self.tokens.get(&id))
+    }
+
+    async fn save(&self, token: &Token) -> Result<(), RepoError> {
+        self.tokens.save_new(&token.id, token);
+        Ok(())
+    }
+
+    async fn update(&self, token: &Token) -> Result<(), RepoError> {
+        self.tokens.update_existing(&token.id, token)?;
+        self.refresh_attempts
+            .lock()
+            .expect("refresh attempts lock poisoned")
+            .remove(&token.id);
+        Ok(())
+    }
+}
+
+#[async_trait]
+impl Repository<NegativeTokenResult> for InMemoryEvmTokenRepository {
+    async fn get(&self, id: TokenId) -> Result<Option<NegativeTokenResult>, RepoError> {
+        Ok(self.negative_results.get(&id))
+    }
+
+    async fn save(&self, result: &NegativeTokenResult) -> Result<(), RepoError> {
+        self.negative_results.save_new(&result.id, result);
+        Ok(())
+    }
+
+    async fn update(&self, result: &NegativeTokenResult) -> Result<(), RepoError> {
+        self.negative_results.update_existing(&result.id, result)
+    }
+}
+
+#[async_trait]
+impl EvmTokenRepository for InMemoryEvmTokenRepository {
+    async fn claim_stale(
+        &self,
+        cutoff: DateTime<Utc>,
+        retry_cutoff: DateTime<Utc>,
+        attempted_at: DateTime<Utc>,
+        limit: usize,
+    ) -> Result<Vec<Token>, RepoError> {
+        // Held throughout, so concurrent claims see each other's attempts.
+        let mut attempts = self
+            .refresh_attempts
+            .lock()
+            .expect("refresh attempts lock poisoned");
+        // Evicted tokens leave their attempts behind.
+        attempts.retain(|id, _| self.tokens.contains(id));
+
+        let mut stale: Vec<Token> = self
+            .tokens
+            .values()
+            .into_iter()
+            .filter(|token| {
+                token.last_verified_at < cutoff
+                    && attempts.get(&token.id).is_none_or(|at| *at < retry_cutoff)
+            })
+            .collect();
+        stale.sort_by_key(|token| token.last_verified_at);
+        stale.truncate(limit);
+        for token in &stale {
+            attempts.insert(token.id.clone(), attempted_at);
+        }
+        Ok(stale)
+    }
+
+    async fn shutdown(&self) -> Result<(), RepoError> {
+        match &self.snapshot_path {
+            Some(path) => self.write_snapshot(path),
+            None => Ok(()),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        repositories::conformance::repository_conformance_tests,
+        token::{MetadataAbi, NegativeReason},
+    };
+
+    repository_conformance_tests!(InMemoryEvmTokenRepository::default());
+
+    mod bounded {
+        use super::*;
+
+        repository_conformance_tests!(InMemoryEvmTokenRepository::new(NonZeroUsize::new(100)));
+    }
+
+    fn token(address_byte: u8) -> Token {
+        let id = format!(
+            "eip155:1/erc20:0x{}",
+            format!("{address_byte:02x}").repeat(20)
+        )
+        .parse()
+        .unwrap();
+        Token::new(
+            id,
+            Some("Token".to_string()),
+            Some("TKN".to_string()),
+            18,
+            MetadataAbi::String,
+        )
+    }
+
+    #[tokio::test]
+    async fn capacity_evicts_least_recently_used_token() {
+        let repository = InMemoryEvmTokenRepository::new(NonZeroUsize::new(2));
+        let (a, b, c) = (token(0x0a), token(0x0b), token(0x0c));
+        repository.save(&a).await.unwrap();
+        repository.save(&b).await.unwrap();
+        // Reading `a` makes `b` the least recently used.
+        Repository::<Token>::get(&repository, a.id.clone())
+            .await
+            .unwrap();
+        repository.save(&c).await.unwrap();
+
+        let stored = |id: TokenId| {
+            let repository = repository.clone();
+            async move {
+                Repository::<Token>::get(&repository, id)
+                    .await
+                    .unwrap()
+                    .is_some()
+            }
+        };
+        assert!(stored(a.id.clone()).await);
+        assert!(!stored(b.id.clone()).await);
+        assert!(stored(c.id.clone()).await);
+    }
+
+    #[tokio::test]
+    async fn snapshot_is_written_on_shutdown_and_loaded_on_start() {
+        let path = std::env::temp_dir().join(format!(
+            "token_api_memory_snapshot_{}.json",
+            std::process::id()
+        ));
+        let _ = fs::remove_file(&path);
+
+        let repository = InMemoryEvmTokenRepository::with_snapshot(None, &path).unwrap();
+        let saved = token(0x0a);
+        repository.save(&saved).await.unwrap();
+        repository
+            .save(&NegativeTokenResult {
+                id: token(0x0b).id,
+                reason: NegativeReason::NotAContract,
+                message: "no code".to_string(),
+                recorded_at: Utc::now(),
+            })
+            .await
+            .unwrap();
+        repository.shutdown().await.unwrap();
+
+        let url = format!("memory:?snapshot={}", path.display());
+        let restored = InMemoryEvmTokenRepository::from_url(&url).unwrap();
+        fs::remove_file(&path).unwrap();
+
+        let restored_token: Token = Repository::<Token>::get(&restored, saved.id.clone())
+            .await
+            .unwrap()
+            .expect("token restored");
+        assert_eq!(restored_token.symbol.as_deref(), Some("TKN"));
+        let negative: Option<NegativeTokenResult> =
+            Repository::<NegativeTokenResult>::get(&restored, token(0x0b).id)
+                .await
+                .unwrap();
+        assert_eq!(negative.unwrap().reason, NegativeReason::NotAContract);
+    }
+
+    #[test]
+    fn from_url_rejects_unknown_parameters() {
+        assert!(InMemoryEvmTokenRepository::from_url("memory:").is_ok());
+        assert!(InMemoryEvmTokenRepository::from_url("memory:?capacity=10").is_ok());
+        assert!(InMemoryEvmTokenRepository::from_url("memory:?capacity=0").is_err());
+        assert!(InMemoryEvmTokenRepository::from_url("memory:?size=10").is_err());
+    }
+}
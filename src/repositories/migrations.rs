@@ -0,0 +1,70 @@
+//! Schema migrations embedded in the binary and applied at startup, so deployments need no diesel
+//! CLI. A database with migrations this binary does not know is refused rather than queried.
+
+use diesel::{backend::Backend, migration::MigrationSource};
+use diesel_migrations::{EmbeddedMigrations, MigrationHarness};
+use log::{info, warn};
+
+use crate::repositories::RepoError;
+
+/// What [`crate::repositories::EvmTokenRepository::migrate`] does with pending migrations.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MigrationMode {
+    /// Apply pending migrations.
+    Apply,
+    /// Only check the schema; pending migrations are logged, not applied.
+    VerifyOnly,
+}
+
+/// Checks `connection` against `migrations` and, with [`MigrationMode::Apply`], runs the pending
+/// ones. Fails if the database has applied migrations that `migrations` does not contain.
+pub(crate) fn run<DB: Backend>(
+    connection: &mut impl MigrationHarness<DB>,
+    migrations: EmbeddedMigrations,
+    mode: MigrationMode,
+) -> Result<(), RepoError> {
+    let known: Vec<String> = MigrationSource::<DB>::migrations(&migrations)
+        .map_err(migration_error)?
+        .iter()
+        .map(|migration| migration.name().version().to_string())
+        .collect();
+
+    let unknown: Vec<String> = connection
+        .applied_migrations()
+        .map_err(migration_error)?
+        .iter()
+        .map(ToString::to_string)
+        .filter(|version| !known.contains(version))
+        .collect();
+    if !unknown.is_empty() {
+        return Err(RepoError::Backend(format!(
+            "Database schema is ahead of this binary; unknown migrations: {}",
+            unknown.join(", ")
+        )));
+    }
+
+    match mode {
+        MigrationMode::Apply => {
+            let applied = connection
+                .run_pending_migrations(migrations)
+                .map_err(migration_error)?;
+            info!("Applied {} pending migrations", applied.len());
+        }
+        MigrationMode::VerifyOnly => {
+            let pending = connection
+                .pending_migrations(migrations)
+                .map_err(migration_error)?;
+            if !pending.is_empty() {
+                warn!(
+                    "{} migrations are pending and were not applied",
+                    pending.len()
+                );
+            }
+        }
+    }
+    Ok(())
+}
+
+fn migration_error(error: Box<dyn std::error::Error + Send + Sync>) -> RepoError {
+    RepoError::Backend(format!("Migration failed: {error}"))
+}
@@ -1,11 +1,28 @@
+pub mod cache;
+pub mod memory;
+pub mod migrations;
+pub mod postgres;
+pub mod redis;
 pub mod sqlite;
 
+#[cfg(test)]
+pub(crate) mod conformance;
+
 use std::{
     error::Error,
     fmt::{self, Display},
+    sync::Arc,
 };
 
-use tap_caip::AccountId;
+use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+
+pub use migrations::MigrationMode;
+
+use crate::{
+    token::{NegativeTokenResult, Token, TokenId, legacy_token_id},
+    types::ChainId,
+};
 
 #[derive(Debug)]
 pub enum RepoError {
@@ -17,7 +34,12 @@ pub enum RepoError {
 
 impl Display for RepoError {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        write!(f, "RepoError: {}", self)
+        match self {
+            RepoError::NotFound => write!(f, "RepoError: not found"),
+            RepoError::Backend(e) => write!(f, "RepoError: backend: {e}"),
+            RepoError::Diesel(e) => write!(f, "RepoError: diesel: {e}"),
+            RepoError::R2d2(e) => write!(f, "RepoError: r2d2: {e}"),
+        }
     }
 }
 
@@ -35,7 +57,112 @@ impl From<diesel::r2d2::Error> for RepoError {
     }
 }
 
-pub trait Repository<T> {
-    fn get(&self, id: AccountId) -> Result<Option<T>, RepoError>;
-    fn save(&self, token: &T) -> Result<(), RepoError>;
+#[async_trait]
+pub trait Repository<T: Sync>: Send + Sync {
+    async fn get(&self, id: TokenId) -> Result<Option<T>, RepoError>;
+    /// Stores `token` if its id is new: insert-if-absent, not an upsert. Saving an id that is
+    /// already stored is not an error and changes none of the stored record's fields, so concurrent
+    /// first lookups cannot overwrite each other; use [`Repository::update`] to overwrite it.
+    async fn save(&self, token: &T) -> Result<(), RepoError>;
+    /// Overwrites the stored record with the same id; [`RepoError::NotFound`] if there is none.
+    async fn update(&self, token: &T) -> Result<(), RepoError>;
+}
+
+/// Everything [`crate::services::evm::EvmTokenService`] stores: tokens, negative results, and the
+/// freshness query behind the background refresh.
+#[async_trait]
+pub trait EvmTokenRepository: Repository<Token> + Repository<NegativeTokenResult> {
+    /// Up to `limit` tokens last verified before `cutoff`, least recently verified first, leaving
+    /// out tokens whose refresh was attempted after `retry_cutoff`. The returned tokens count as
+    /// attempted at `attempted_at` until [`Repository::update`] stores them again, so tokens that
+    /// keep failing do not hold the head of the queue, and replicas refreshing at the same time
+    /// claim different tokens.
+    async fn claim_stale(
+        &self,
+        cutoff: DateTime<Utc>,
+        retry_cutoff: DateTime<Utc>,
+        attempted_at: DateTime<Utc>,
+        limit: usize,
+    ) -> Result<Vec<Token>, RepoError>;
+
+    /// Checks the storage schema against the migrations embedded in this binary and, with
+    /// [`MigrationMode::Apply`], brings it up to date. Backends without a schema accept any mode.
+    async fn migrate(&self, _mode: MigrationMode) -> Result<(), RepoError> {
+        Ok(())
+    }
+
+    /// Called once when the server stops, e.g. to persist state held only in memory.
+    async fn shutdown(&self) -> Result<(), RepoError> {
+        Ok(())
+    }
+}
+
+pub type DynEvmTokenRepository = Arc<dyn EvmTokenRepository>;
+
+/// Storage backend, selected by the scheme of the database URL.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RepositoryBackend {
+    /// A file path or `file:` URI.
+    Sqlite,
+    /// `postgres://` / `postgresql://` URLs.
+    Postgres,
+    /// `memory:` URLs (see [`memory::evm_token::InMemoryEvmTokenRepository::from_url`]).
+    Memory,
+}
+
+impl RepositoryBackend {
+    pub fn for_url(database_url: &str) -> Self {
+        if database_url.starts_with("memory:") {
+            RepositoryBackend::Memory
+        } else if is_postgres_url(database_url) {
+            RepositoryBackend::Postgres
+        } else {
+            RepositoryBackend::Sqlite
+        }
+    }
+}
+
+/// Token repository for `database_url`, on the backend given by [`RepositoryBackend::for_url`].
+pub fn evm_token_repository_from_url(
+    database_url: String,
+) -> Result<DynEvmTokenRepository, RepoError> {
+    Ok(match RepositoryBackend::for_url(&database_url) {
+        RepositoryBackend::Memory => Arc::new(
+            memory::evm_token::InMemoryEvmTokenRepository::from_url(&database_url)?,
+        ),
+        RepositoryBackend::Postgres => {
+            Arc::new(postgres::evm_token::PgEvmTokenRepository::new(database_url))
+        }
+        RepositoryBackend::Sqlite => Arc::new(sqlite::evm_token::SqliteEvmTokenRepository::new(
+            database_url,
+        )),
+    })
+}
+
+pub fn is_postgres_url(database_url: &str) -> bool {
+    database_url.starts_with("postgres://") || database_url.starts_with("postgresql://")
+}
+
+/// Id a token is written under in SQL. ERC-20 tokens keep their CAIP-10 form until a cutover
+/// migration rewrites stored ids, so replicas from before the move to CAIP-19 can still read every
+/// row during a rolling deploy, and a saved token meets its existing row on the primary key.
+pub(crate) fn storage_id(id: &TokenId) -> String {
+    legacy_token_id(id).unwrap_or_else(|| id.to_string())
+}
+
+/// Ids a token may be stored under in SQL: its CAIP-19 id and, for ERC-20 tokens, the CAIP-10 form
+/// [`storage_id`] writes. Reads and updates match either, so the cutover can rewrite ids while the
+/// server runs.
+pub(crate) fn stored_ids(id: &TokenId) -> Vec<String> {
+    std::iter::once(id.to_string())
+        .chain(legacy_token_id(id))
+        .collect()
+}
+
+/// Numeric EVM chain id of a CAIP-19 token id, as stored alongside it.
+pub(crate) fn chain_id_of(id: &TokenId) -> Result<ChainId, RepoError> {
+    id.chain_id()
+        .reference()
+        .parse::<ChainId>()
+        .map_err(|e| RepoError::Backend(format!("Failed to parse chain id: {}", e)))
 }
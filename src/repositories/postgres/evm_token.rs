@@ -0,0 +1,331 @@
+use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+use diesel::prelude::*;
+use diesel::r2d2::{ConnectionManager, Pool};
+use log::{debug, info};
+
+use super::schema::{evm_token_negative_results, evm_tokens};
+use crate::{
+    repositories::{
+        EvmTokenRepository, MigrationMode, RepoError, Repository, chain_id_of, migrations,
+        storage_id, stored_ids,
+    },
+    token::{MetadataAbi, NegativeReason, NegativeTokenResult, Token, TokenId, parse_token_id},
+};
+
+#[derive(Queryable, Insertable, AsChangeset)]
+#[diesel(table_name = evm_tokens, treat_none_as_null = true)]
+pub struct PgEvmToken {
+    pub id: String,
+    pub chain_id: i64,
+    pub address: String,
+    pub symbol: Option<String>,
+    pub decimals: i32,
+    pub name: Option<String>,
+    pub metadata_abi: String,
+    pub fetched_at: DateTime<Utc>,
+    pub last_verified_at: DateTime<Utc>,
+    pub source_block: Option<i64>,
+    pub refresh_attempted_at: Option<DateTime<Utc>>,
+}
+
+#[derive(Queryable, Insertable, AsChangeset)]
+#[diesel(table_name = evm_token_negative_results)]
+pub struct PgEvmTokenNegativeResult {
+    pub id: String,
+    pub chain_id: i64,
+    pub address: String,
+    pub reason: String,
+    pub message: String,
+    pub recorded_at: DateTime<Utc>,
+}
+
+/// Token storage shared by several replicas; schema and migrations live in `migrations_postgres`.
+#[derive(Clone)]
+pub struct PgEvmTokenRepository {
+    pool: Pool<ConnectionManager<PgConnection>>,
+}
+
+impl PgEvmTokenRepository {
+    pub fn new(database_url: String) -> Self {
+        let manager = ConnectionManager::<PgConnection>::new(database_url);
+
+        let pool = Pool::builder()
+            .build(manager)
+            .expect("Could not build connection pool");
+
+        debug!("Connected to PostgreSQL database");
+
+        Self { pool }
+    }
+
+    /// Runs blocking Diesel work `f` with a pooled connection on Tokio's blocking thread pool.
+    async fn with_connection<R, F>(&self, f: F) -> Result<R, RepoError>
+    where
+        R: Send + 'static,
+        F: FnOnce(&mut PgConnection) -> Result<R, RepoError> + Send + 'static,
+    {
+        let pool = self.pool.clone();
+        tokio::task::spawn_blocking(move || {
+            let mut connection = pool.get().map_err(|e| RepoError::Backend(e.to_string()))?;
+            f(&mut connection)
+        })
+        .await
+        .map_err(|e| RepoError::Backend(e.to_string()))?
+    }
+}
+
+#[async_trait]
+impl Repository<Token> for PgEvmTokenRepository {
+    async fn get(&self, id: TokenId) -> Result<Option<Token>, RepoError> {
+        debug!("Finding EVM token by id: {:?}", id.to_string());
+
+        let token: Option<PgEvmToken> = self
+            .with_connection(move |connection| {
+                Ok(evm_tokens::table
+                    .filter(evm_tokens::id.eq_any(stored_ids(&id)))
+                    .first::<PgEvmToken>(connection)
+                    .optional()?)
+            })
+            .await?;
+
+        token.map(Token::try_from).transpose()
+    }
+
+    async fn save(&self, token: &Token) -> Result<(), RepoError> {
+        info!("Saving EVM token with id: {:?}", token.id);
+
+        let row = PgEvmToken::try_from(token)?;
+        self.with_connection(move |connection| {
+            // Replicas racing on the same token resolve it identically; the first stored copy wins.
+            diesel::insert_into(evm_tokens::table)
+                .values(&row)
+                .on_conflict(evm_tokens::id)
+                .do_nothing()
+                .execute(connection)?;
+            Ok(())
+        })
+        .await
+    }
+
+    async fn update(&self, token: &Token) -> Result<(), RepoError> {
+        info!("Updating EVM token with id: {:?}", token.id);
+
+        let row = PgEvmToken::try_from(token)?;
+        let ids = stored_ids(&token.id);
+        let updated = self
+            .with_connection(move |connection| {
+                // A row still under its CAIP-10 id is updated in place.
+                for id in ids {
+                    let updated = diesel::update(evm_tokens::table.find(id))
+                        .set(&row)
+                        .execute(connection)?;
+                    if updated > 0 {
+                        return Ok(updated);
+                    }
+                }
+                Ok(0)
+            })
+            .await?;
+
+        if updated == 0 {
+            return Err(RepoError::NotFound);
+        }
+
+        Ok(())
+    }
+}
+
+#[async_trait]
+impl EvmTokenRepository for PgEvmTokenRepository {
+    async fn claim_stale(
+        &self,
+        cutoff: DateTime<Utc>,
+        retry_cutoff: DateTime<Utc>,
+        attempted_at: DateTime<Utc>,
+        limit: usize,
+    ) -> Result<Vec<Token>, RepoError> {
+        let limit = i64::try_from(limit).unwrap_or(i64::MAX);
+        let rows = self
+            .with_connection(move |connection| {
+                // Rows another replica is claiming right now are skipped rather than waited for.
+                Ok(connection.transaction(|connection| {
+                    let rows = evm_tokens::table
+                        .filter(evm_tokens::last_verified_at.lt(cutoff))
+                        .filter(
+                            evm_tokens::refresh_attempted_at
+                                .is_null()
+                                .or(evm_tokens::refresh_attempted_at.lt(retry_cutoff)),
+                        )
+                        .order(evm_tokens::last_verified_at.asc())
+                        .limit(limit)
+                        .for_update()
+                        .skip_locked()
+                        .load::<PgEvmToken>(connection)?;
+                    let ids: Vec<&str> = rows.iter().map(|row| row.id.as_str()).collect();
+                    diesel::update(evm_tokens::table.filter(evm_tokens::id.eq_any(ids)))
+                        .set(evm_tokens::refresh_attempted_at.eq(attempted_at))
+                        .execute(connection)?;
+                    Ok::<_, diesel::result::Error>(rows)
+                })?)
+            })
+            .await?;
+
+        rows.into_iter().map(Token::try_from).collect()
+    }
+
+    async fn migrate(&self, mode: MigrationMode) -> Result<(), RepoError> {
+        self.with_connection(move |connection| migrations::run(connection, super::MIGRATIONS, mode))
+            .await
+    }
+}
+
+#[async_trait]
+impl Repository<NegativeTokenResult> for PgEvmTokenRepository {
+    async fn get(&self, id: TokenId) -> Result<Option<NegativeTokenResult>, RepoError> {
+        debug!("Finding negative result by id: {:?}", id.to_string());
+
+        let keys = stored_ids(&id);
+        let result: Option<PgEvmTokenNegativeResult> = self
+            .with_connection(move |connection| {
+                Ok(evm_token_negative_results::table
+                    .filter(evm_token_negative_results::id.eq_any(keys))
+                    .first::<PgEvmTokenNegativeResult>(connection)
+                    .optional()?)
+            })
+            .await?;
+
+        let Some(result) = result else {
+            return Ok(None);
+        };
+
+        let reason = result
+            .reason
+            .parse::<NegativeReason>()
+            .map_err(RepoError::Backend)?;
+
+        Ok(Some(NegativeTokenResult {
+            id,
+            reason,
+            message: result.message,
+            recorded_at: result.recorded_at,
+        }))
+    }
+
+    async fn save(&self, result: &NegativeTokenResult) -> Result<(), RepoError> {
+        info!(
+            "Saving negative result for {:?}: {}",
+            result.id.to_string(),
+            result.reason
+        );
+
+        let row = PgEvmTokenNegativeResult::try_from(result)?;
+        self.with_connection(move |connection| {
+            diesel::insert_into(evm_token_negative_results::table)
+                .values(&row)
+                .on_conflict(evm_token_negative_results::id)
+                .do_nothing()
+                .execute(connection)?;
+            Ok(())
+        })
+        .await
+    }
+
+    async fn update(&self, result: &NegativeTokenResult) -> Result<(), RepoError> {
+        let row = PgEvmTokenNegativeResult::try_from(result)?;
+        let ids = stored_ids(&result.id);
+        let updated = self
+            .with_connection(move |connection| {
+                for id in ids {
+                    let updated = diesel::update(evm_token_negative_results::table.find(id))
+                        .set(&row)
+                        .execute(connection)?;
+                    if updated > 0 {
+                        return Ok(updated);
+                    }
+                }
+                Ok(0)
+            })
+            .await?;
+
+        if updated == 0 {
+            return Err(RepoError::NotFound);
+        }
+
+        Ok(())
+    }
+}
+
+impl TryFrom<PgEvmToken> for Token {
+    type Error = RepoError;
+
+    fn try_from(token: PgEvmToken) -> Result<Self, Self::Error> {
+        let id = parse_token_id(&token.id)
+            .map_err(|e| RepoError::Backend(format!("Invalid token id {}: {e}", token.id)))?;
+
+        let metadata_abi = token
+            .metadata_abi
+            .parse::<MetadataAbi>()
+            .map_err(RepoError::Backend)?;
+
+        let mut result = Token::new(
+            id,
+            token.name,
+            token.symbol,
+            token.decimals as u8,
+            metadata_abi,
+        );
+        result.fetched_at = token.fetched_at;
+        result.last_verified_at = token.last_verified_at;
+        result.source_block = token.source_block.map(|block| block as u64);
+
+        Ok(result)
+    }
+}
+
+impl TryFrom<&Token> for PgEvmToken {
+    type Error = RepoError;
+
+    fn try_from(token: &Token) -> Result<Self, Self::Error> {
+        Ok(PgEvmToken {
+            id: storage_id(&token.id),
+            chain_id: chain_id_of(&token.id)?,
+            address: token.id.reference().to_string(),
+            symbol: token.symbol.clone(),
+            decimals: token.decimals as i32,
+            name: token.name.clone(),
+            metadata_abi: token.metadata_abi.to_string(),
+            fetched_at: token.fetched_at,
+            last_verified_at: token.last_verified_at,
+            source_block: token.source_block.map(|block| block as i64),
+            refresh_attempted_at: None,
+        })
+    }
+}
+
+impl TryFrom<&NegativeTokenResult> for PgEvmTokenNegativeResult {
+    type Error = RepoError;
+
+    fn try_from(result: &NegativeTokenResult) -> Result<Self, Self::Error> {
+        Ok(PgEvmTokenNegativeResult {
+            id: storage_id(&result.id),
+            chain_id: chain_id_of(&result.id)?,
+            address: result.id.reference().to_string(),
+            reason: result.reason.to_string(),
+            message: result.message.clone(),
+            recorded_at: result.recorded_at,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::repositories::{
+        conformance::repository_conformance_tests, postgres::test_support::migrated_repository,
+    };
+
+    repository_conformance_tests!(
+        migrated_repository(),
+        #[ignore = "needs PostgreSQL at TEST_POSTGRES_URL"]
+    );
+}
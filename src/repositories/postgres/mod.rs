@@ -0,0 +1,10 @@
+use diesel_migrations::{EmbeddedMigrations, embed_migrations};
+
+pub mod evm_token;
+mod schema;
+
+#[cfg(test)]
+pub(crate) mod test_support;
+
+/// The `migrations_postgres` directory, embedded at build time.
+pub const MIGRATIONS: EmbeddedMigrations = embed_migrations!("migrations_postgres");
@@ -0,0 +1,123 @@
+//! Throwaway PostgreSQL databases for tests, created on the server at `TEST_POSTGRES_URL`
+//! (e.g. `postgres://postgres@localhost/postgres`) and dropped when the test ends. Tests using them
+//! are `#[ignore]`d; run with `TEST_POSTGRES_URL=... cargo test -- --ignored postgres`.
+
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+use diesel::{connection::SimpleConnection, prelude::*};
+use diesel_migrations::MigrationHarness;
+use log::warn;
+use url::Url;
+
+use super::{MIGRATIONS, evm_token::PgEvmTokenRepository};
+use crate::{
+    repositories::{EvmTokenRepository, MigrationMode, RepoError, Repository},
+    token::{NegativeTokenResult, Token, TokenId},
+};
+
+/// [`PgEvmTokenRepository`] over a database of its own, dropped with it.
+pub(crate) struct TestDatabase {
+    repository: PgEvmTokenRepository,
+    admin_url: String,
+    name: String,
+}
+
+impl Drop for TestDatabase {
+    fn drop(&mut self) {
+        // `FORCE` closes the repository's pooled connections, which are still open here.
+        let dropped = match PgConnection::establish(&self.admin_url) {
+            Ok(mut admin) => admin
+                .batch_execute(&format!(
+                    "DROP DATABASE IF EXISTS {} WITH (FORCE)",
+                    self.name
+                ))
+                .map_err(|e| e.to_string()),
+            Err(e) => Err(e.to_string()),
+        };
+        if let Err(e) = dropped {
+            warn!("Could not drop test database {}: {e}", self.name);
+        }
+    }
+}
+
+#[async_trait]
+impl Repository<Token> for TestDatabase {
+    async fn get(&self, id: TokenId) -> Result<Option<Token>, RepoError> {
+        Repository::<Token>::get(&self.repository, id).await
+    }
+
+    async fn save(&self, token: &Token) -> Result<(), RepoError> {
+        self.repository.save(token).await
+    }
+
+    async fn update(&self, token: &Token) -> Result<(), RepoError> {
+        self.repository.update(token).await
+    }
+}
+
+#[async_trait]
+impl Repository<NegativeTokenResult> for TestDatabase {
+    async fn get(&self, id: TokenId) -> Result<Option<NegativeTokenResult>, RepoError> {
+        Repository::<NegativeTokenResult>::get(&self.repository, id).await
+    }
+
+    async fn save(&self, result: &NegativeTokenResult) -> Result<(), RepoError> {
+        self.repository.save(result).await
+    }
+
+    async fn update(&self, result: &NegativeTokenResult) -> Result<(), RepoError> {
+        self.repository.update(result).await
+    }
+}
+
+#[async_trait]
+impl EvmTokenRepository for TestDatabase {
+    async fn claim_stale(
+        &self,
+        cutoff: DateTime<Utc>,
+        retry_cutoff: DateTime<Utc>,
+        attempted_at: DateTime<Utc>,
+        limit: usize,
+    ) -> Result<Vec<Token>, RepoError> {
+        self.repository
+            .claim_stale(cutoff, retry_cutoff, attempted_at, limit)
+            .await
+    }
+
+    async fn migrate(&self, mode: MigrationMode) -> Result<(), RepoError> {
+        self.repository.migrate(mode).await
+    }
+}
+
+/// Repository over a new, empty database with all migrations applied.
+pub(crate) fn migrated_repository() -> TestDatabase {
+    static NEXT: AtomicUsize = AtomicUsize::new(0);
+
+    let admin_url = std::env::var("TEST_POSTGRES_URL").expect("TEST_POSTGRES_URL must be set");
+    let name = format!(
+        "token_api_test_{}_{}",
+        std::process::id(),
+        NEXT.fetch_add(1, Ordering::Relaxed)
+    );
+
+    let mut admin = PgConnection::establish(&admin_url).expect("connect to TEST_POSTGRES_URL");
+    admin
+        .batch_execute(&format!("CREATE DATABASE {name}"))
+        .expect("create test database");
+
+    let mut url = Url::parse(&admin_url).expect("TEST_POSTGRES_URL must be a URL");
+    url.set_path(&name);
+
+    let mut connection = PgConnection::establish(url.as_str()).expect("open test database");
+    connection
+        .run_pending_migrations(MIGRATIONS)
+        .expect("apply test migrations");
+
+    TestDatabase {
+        repository: PgEvmTokenRepository::new(url.into()),
+        admin_url,
+        name,
+    }
+}
@@ -0,0 +1,336 @@
+//! Redis cache tier shared by all replicas, between the service and the durable repository.
+//!
+//! Keys are `{prefix}:v2:token:{token_id}` for tokens and `{prefix}:v2:negative:{token_id}` for
+//! negative results, where `token_id` is the CAIP-19 id (e.g.
+//! `token-api:v2:token:eip155:1/erc20:0xa0b8…eb48`); `v1` keys held CAIP-10 ids. Values are the
+//! records as JSON and expire after the configured TTL. Redis failures are logged and fall back to
+//! the durable repository.
+
+use std::time::Duration;
+
+use ::redis::{
+    AsyncCommands, Client, ExistenceCheck, SetExpiry, SetOptions, aio::ConnectionManager,
+};
+use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+use log::{debug, warn};
+use serde::{Serialize, de::DeserializeOwned};
+
+use crate::{
+    repositories::{
+        DynEvmTokenRepository, EvmTokenRepository, MigrationMode, RepoError, Repository,
+    },
+    token::{NegativeTokenResult, Token, TokenId},
+};
+
+/// Default key prefix; bump the `v2` segment of [`key`] when the JSON shape changes.
+pub const DEFAULT_KEY_PREFIX: &str = "token-api";
+
+pub struct RedisCachedEvmTokenRepository {
+    inner: DynEvmTokenRepository,
+    connection: ConnectionManager,
+    key_prefix: String,
+    ttl: Duration,
+}
+
+/// Kind of record stored under a key.
+#[derive(Clone, Copy)]
+enum Record {
+    Token,
+    Negative,
+}
+
+fn key(prefix: &str, record: Record, id: &TokenId) -> String {
+    let record = match record {
+        Record::Token => "token",
+        Record::Negative => "negative",
+    };
+    format!("{prefix}:v2:{record}:{id}")
+}
+
+impl RedisCachedEvmTokenRepository {
+    /// Caches `inner` in the Redis server at `redis_url`, entries expiring after `ttl` (in whole
+    /// seconds, at least one).
+    pub async fn connect(
+        inner: DynEvmTokenRepository,
+        redis_url: &str,
+        key_prefix: impl Into<String>,
+        ttl: Duration,
+    ) -> Result<Self, RepoError> {
+        let client = Client::open(redis_url)
+            .map_err(|e| RepoError::Backend(format!("Invalid Redis URL: {e}")))?;
+        let connection = ConnectionManager::new(client)
+            .await
+            .map_err(|e| RepoError::Backend(format!("Failed to connect to Redis: {e}")))?;
+
+        Ok(Self {
+            inner,
+            connection,
+            key_prefix: key_prefix.into(),
+            ttl,
+        })
+    }
+
+    /// Cached record under `id`; `None` on a miss or when Redis is unavailable.
+    async fn cached<T: DeserializeOwned>(&self, record: Record, id: &TokenId) -> Option<T> {
+        let key = key(&self.key_prefix, record, id);
+        let json: Option<String> = match self.connection.clone().get(&key).await {
+            Ok(json) => json,
+            Err(e) => {
+                warn!("Redis GET {key} failed: {e}");
+                return None;
+            }
+        };
+
+        match serde_json::from_str(&json?) {
+            Ok(value) => Some(value),
+            Err(e) => {
+                warn!("Ignoring undecodable Redis value at {key}: {e}");
+                None
+            }
+        }
+    }
+
+    /// Best-effort `SET` of `value`; with `ExistenceCheck::NX` an existing entry is kept.
+    async fn store<T: Serialize>(
+        &self,
+        record: Record,
+        id: &TokenId,
+        value: &T,
+        existence: Option<ExistenceCheck>,
+    ) {
+        let key = key(&self.key_prefix, record, id);
+        let json = match serde_json::to_string(value) {
+            Ok(json) => json,
+            Err(e) => {
+                warn!("Failed to encode Redis value for {key}: {e}");
+                return;
+            }
+        };
+
+        // Redis rejects `EX 0`.
+        let expiry = SetExpiry::EX(self.ttl.as_secs().max(1));
+        let mut options = SetOptions::default().with_expiration(expiry);
+        if let Some(existence) = existence {
+            options = options.conditional_set(existence);
+        }
+
+        let set: Result<Option<String>, _> = self
+            .connection
+            .clone()
+            .set_options(&key, json, options)
+            .await;
+        if let Err(e) = set {
+            warn!("Redis SET {key} failed: {e}");
+        }
+    }
+
+    /// Read-through `get` of one record kind.
+    async fn get_through<T>(&self, record: Record, id: TokenId) -> Result<Option<T>, RepoError>
+    where
+        T: Serialize + DeserializeOwned + Sync,
+        dyn EvmTokenRepository: Repository<T>,
+    {
+        if let Some(value) = self.cached(record, &id).await {
+            debug!("Redis hit for {id}");
+            return Ok(Some(value));
+        }
+
+        let value = Repository::<T>::get(self.inner.as_ref(), id.clone()).await?;
+        if let Some(value) = &value {
+            self.store(record, &id, value, None).await;
+        }
+        Ok(value)
+    }
+}
+
+#[async_trait]
+impl Repository<Token> for RedisCachedEvmTokenRepository {
+    async fn get(&self, id: TokenId) -> Result<Option<Token>, RepoError> {
+        self.get_through(Record::Token, id).await
+    }
+
+    async fn save(&self, token: &Token) -> Result<(), RepoError> {
+        Repository::<Token>::save(self.inner.as_ref(), token).await?;
+        self.store(Record::Token, &token.id, token, Some(ExistenceCheck::NX))
+            .await;
+        Ok(())
+    }
+
+    async fn update(&self, token: &Token) -> Result<(), RepoError> {
+        Repository::<Token>::update(self.inner.as_ref(), token).await?;
+        self.store(Record::Token, &token.id, token, None).await;
+        Ok(())
+    }
+}
+
+#[async_trait]
+impl Repository<NegativeTokenResult> for RedisCachedEvmTokenRepository {
+    async fn get(&self, id: TokenId) -> Result<Option<NegativeTokenResult>, RepoError> {
+        self.get_through(Record::Negative, id).await
+    }
+
+    async fn save(&self, result: &NegativeTokenResult) -> Result<(), RepoError> {
+        self.inner.save(result).await?;
+        self.store(
+            Record::Negative,
+            &result.id,
+            result,
+            Some(ExistenceCheck::NX),
+        )
+        .await;
+        Ok(())
+    }
+
+    async fn update(&self, result: &NegativeTokenResult) -> Result<(), RepoError> {
+        self.inner.update(result).await?;
+        self.store(Record::Negative, &result.id, result, None).await;
+        Ok(())
+    }
+}
+
+#[async_trait]
+impl EvmTokenRepository for RedisCachedEvmTokenRepository {
+    async fn claim_stale(
+        &self,
+        cutoff: DateTime<Utc>,
+        retry_cutoff: DateTime<Utc>,
+        attempted_at: DateTime<Utc>,
+        limit: usize,
+    ) -> Result<Vec<Token>, RepoError> {
+        self.inner
+            .claim_stale(cutoff, retry_cutoff, attempted_at, limit)
+            .await
+    }
+
+    async fn migrate(&self, mode: MigrationMode) -> Result<(), RepoError> {
+        self.inner.migrate(mode).await
+    }
+
+    async fn shutdown(&self) -> Result<(), RepoError> {
+        self.inner.shutdown().await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+
+    use super::*;
+    use crate::{
+        repositories::{
+            conformance::repository_conformance_tests,
+            memory::evm_token::InMemoryEvmTokenRepository,
+            redis::test_support::{RespStandIn, cached_repository, real_redis_repository},
+        },
+        token::MetadataAbi,
+    };
+
+    repository_conformance_tests!(cached_repository(&RespStandIn::start().await).await.1);
+
+    mod real_redis {
+        use super::*;
+
+        repository_conformance_tests!(
+            real_redis_repository().await,
+            #[ignore = "needs Redis at TEST_REDIS_URL"]
+        );
+    }
+
+    fn token(address_byte: u8, symbol: &str) -> Token {
+        let id = format!(
+            "eip155:1/erc20:0x{}",
+            format!("{address_byte:02x}").repeat(20)
+        )
+        .parse()
+        .unwrap();
+        Token::new(
+            id,
+            Some("Token".to_string()),
+            Some(symbol.to_string()),
+            18,
+            MetadataAbi::String,
+        )
+    }
+
+    #[test]
+    fn keys_are_prefixed_caip19_ids() {
+        let id = token(0xab, "TKN").id;
+        assert_eq!(
+            key(DEFAULT_KEY_PREFIX, Record::Token, &id),
+            format!("token-api:v2:token:eip155:1/erc20:0x{}", "ab".repeat(20))
+        );
+        assert_eq!(
+            key("staging", Record::Negative, &id),
+            format!("staging:v2:negative:eip155:1/erc20:0x{}", "ab".repeat(20))
+        );
+    }
+
+    #[tokio::test]
+    async fn replicas_share_tokens_saved_by_one_of_them() {
+        let redis = RespStandIn::start().await;
+        let (_, first) = cached_repository(&redis).await;
+        let saved = token(0x0a, "TKN");
+        first.save(&saved).await.unwrap();
+
+        // A second replica with its own, empty durable repository.
+        let second = RedisCachedEvmTokenRepository::connect(
+            Arc::new(InMemoryEvmTokenRepository::default()),
+            &redis.url(),
+            DEFAULT_KEY_PREFIX,
+            Duration::from_secs(60),
+        )
+        .await
+        .unwrap();
+        let shared: Token = Repository::<Token>::get(&second, saved.id.clone())
+            .await
+            .unwrap()
+            .expect("token served from Redis");
+        assert_eq!(shared.symbol.as_deref(), Some("TKN"));
+
+        let json = redis
+            .value(&key(DEFAULT_KEY_PREFIX, Record::Token, &saved.id))
+            .expect("token stored as JSON");
+        let stored: serde_json::Value = serde_json::from_str(&json).unwrap();
+        assert_eq!(stored["symbol"], "TKN");
+    }
+
+    #[tokio::test]
+    async fn undecodable_values_fall_back_to_the_repository() {
+        let redis = RespStandIn::start().await;
+        let (inner, repository) = cached_repository(&redis).await;
+        let saved = token(0x0a, "TKN");
+        inner.save(&saved).await.unwrap();
+        redis.insert(
+            &key(DEFAULT_KEY_PREFIX, Record::Token, &saved.id),
+            "not json",
+        );
+
+        let token: Token = Repository::<Token>::get(&repository, saved.id.clone())
+            .await
+            .unwrap()
+            .expect("token from repository");
+        assert_eq!(token.symbol.as_deref(), Some("TKN"));
+    }
+
+    #[tokio::test]
+    async fn sub_second_ttls_are_stored_with_one_second_expiry() {
+        let redis = RespStandIn::start().await;
+        let repository = RedisCachedEvmTokenRepository::connect(
+            Arc::new(InMemoryEvmTokenRepository::default()),
+            &redis.url(),
+            DEFAULT_KEY_PREFIX,
+            Duration::from_millis(500),
+        )
+        .await
+        .unwrap();
+        let saved = token(0x0a, "TKN");
+        repository.save(&saved).await.unwrap();
+
+        assert!(
+            redis
+                .value(&key(DEFAULT_KEY_PREFIX, Record::Token, &saved.id))
+                .is_some()
+        );
+    }
+}
@@ -0,0 +1,4 @@
+pub mod evm_token;
+
+#[cfg(test)]
+pub(crate) mod test_support;
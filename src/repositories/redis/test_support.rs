@@ -0,0 +1,171 @@
+//! Redis for tests: [`RespStandIn`], an in-process server speaking just enough RESP2 for the cache
+//! (`GET`, `SET` with `NX`/`EX`, `DEL`, `PING`), or a real server at `TEST_REDIS_URL` (e.g.
+//! `redis://localhost`) for `#[ignore]`d tests run with `TEST_REDIS_URL=... cargo test -- --ignored
+//! redis`.
+
+use std::{
+    collections::HashMap,
+    sync::{
+        Arc, Mutex,
+        atomic::{AtomicUsize, Ordering},
+    },
+    time::Duration,
+};
+
+use tokio::{
+    io::{AsyncBufReadExt, AsyncReadExt, AsyncWriteExt, BufReader},
+    net::{TcpListener, TcpStream},
+};
+
+use super::evm_token::{DEFAULT_KEY_PREFIX, RedisCachedEvmTokenRepository};
+use crate::repositories::memory::evm_token::InMemoryEvmTokenRepository;
+
+type Store = Arc<Mutex<HashMap<String, String>>>;
+
+/// In-process RESP server on a random local port, alive until the test's runtime shuts down.
+/// Expiry is validated like Redis does, then ignored.
+pub(crate) struct RespStandIn {
+    port: u16,
+    store: Store,
+}
+
+impl RespStandIn {
+    pub(crate) async fn start() -> Self {
+        let listener = TcpListener::bind("127.0.0.1:0")
+            .await
+            .expect("bind RESP stand-in");
+        let port = listener.local_addr().expect("local address").port();
+        let store = Store::default();
+
+        let served = store.clone();
+        tokio::spawn(async move {
+            while let Ok((stream, _)) = listener.accept().await {
+                tokio::spawn(serve(stream, served.clone()));
+            }
+        });
+
+        Self { port, store }
+    }
+
+    pub(crate) fn url(&self) -> String {
+        format!("redis://127.0.0.1:{}", self.port)
+    }
+
+    pub(crate) fn value(&self, key: &str) -> Option<String> {
+        self.store.lock().unwrap().get(key).cloned()
+    }
+
+    pub(crate) fn insert(&self, key: &str, value: &str) {
+        self.store
+            .lock()
+            .unwrap()
+            .insert(key.to_string(), value.to_string());
+    }
+}
+
+async fn serve(stream: TcpStream, store: Store) {
+    let (read, mut write) = stream.into_split();
+    let mut read = BufReader::new(read);
+
+    while let Some(command) = read_command(&mut read).await {
+        let reply = execute(&command, &store);
+        if write.write_all(reply.as_bytes()).await.is_err() {
+            return;
+        }
+    }
+}
+
+/// One RESP array of bulk strings; `None` on EOF or malformed input.
+async fn read_command<R: AsyncBufReadExt + Unpin>(read: &mut R) -> Option<Vec<String>> {
+    let count: usize = read_line(read).await?.strip_prefix('*')?.parse().ok()?;
+    let mut command = Vec::with_capacity(count);
+    for _ in 0..count {
+        let len: usize = read_line(read).await?.strip_prefix('$')?.parse().ok()?;
+        let mut bulk = vec![0; len + 2];
+        read.read_exact(&mut bulk).await.ok()?;
+        bulk.truncate(len);
+        command.push(String::from_utf8(bulk).ok()?);
+    }
+    Some(command)
+}
+
+async fn read_line<R: AsyncBufReadExt + Unpin>(read: &mut R) -> Option<String> {
+    let mut line = String::new();
+    match read.read_line(&mut line).await {
+        Ok(0) | Err(_) => None,
+        Ok(_) => Some(line.trim_end().to_string()),
+    }
+}
+
+fn execute(command: &[String], store: &Store) -> String {
+    let mut store = store.lock().unwrap();
+    let args = &command[1..];
+    match command[0].to_uppercase().as_str() {
+        "PING" => "+PONG\r\n".to_string(),
+        "CLIENT" => "+OK\r\n".to_string(),
+        "GET" => match store.get(&args[0]) {
+            Some(value) => format!("${}\r\n{value}\r\n", value.len()),
+            None => "$-1\r\n".to_string(),
+        },
+        "SET" => {
+            let ex = args[2..]
+                .windows(2)
+                .find(|option| option[0].eq_ignore_ascii_case("EX"));
+            if ex.is_some_and(|option| option[1].parse::<u64>().is_ok_and(|secs| secs == 0)) {
+                return "-ERR invalid expire time in 'set' command\r\n".to_string();
+            }
+            let nx = args[2..].iter().any(|arg| arg.eq_ignore_ascii_case("NX"));
+            if nx && store.contains_key(&args[0]) {
+                return "$-1\r\n".to_string();
+            }
+            store.insert(args[0].clone(), args[1].clone());
+            "+OK\r\n".to_string()
+        }
+        "DEL" => {
+            let removed = args.iter().filter(|key| store.remove(*key).is_some());
+            format!(":{}\r\n", removed.count())
+        }
+        other => format!("-ERR unknown command '{other}'\r\n"),
+    }
+}
+
+/// Redis cache over an empty in-memory repository, which is returned for setting up state behind
+/// the cache.
+pub(crate) async fn cached_repository(
+    redis: &RespStandIn,
+) -> (
+    Arc<InMemoryEvmTokenRepository>,
+    RedisCachedEvmTokenRepository,
+) {
+    let inner = Arc::new(InMemoryEvmTokenRepository::default());
+    let repository = RedisCachedEvmTokenRepository::connect(
+        inner.clone(),
+        &redis.url(),
+        DEFAULT_KEY_PREFIX,
+        Duration::from_secs(60),
+    )
+    .await
+    .expect("connect to RESP stand-in");
+    (inner, repository)
+}
+
+/// Redis cache on the server at `TEST_REDIS_URL`, under a key prefix unique to this call.
+pub(crate) async fn real_redis_repository() -> RedisCachedEvmTokenRepository {
+    static NEXT: AtomicUsize = AtomicUsize::new(0);
+
+    let url = std::env::var("TEST_REDIS_URL").expect("TEST_REDIS_URL must be set");
+    let prefix = format!(
+        "token_api_test_{}_{}",
+        std::process::id(),
+        NEXT.fetch_add(1, Ordering::Relaxed)
+    );
+
+    RedisCachedEvmTokenRepository::connect(
+        Arc::new(InMemoryEvmTokenRepository::default()),
+        &url,
+        prefix,
+        Duration::from_secs(60),
+    )
+    .await
+    .expect("connect to TEST_REDIS_URL")
+}
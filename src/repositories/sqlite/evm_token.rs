@@ -1,23 +1,42 @@
+use async_trait::async_trait;
+use chrono::{DateTime, Utc};
 use diesel::prelude::*;
-use diesel::r2d2::{ConnectionManager, Pool, PooledConnection};
+use diesel::r2d2::{ConnectionManager, Pool};
 use log::{debug, info};
-use tap_caip::AccountId;
 
 use crate::{
-    repositories::{RepoError, Repository},
-    token::Token,
-    types::ChainId,
+    repositories::{
+        EvmTokenRepository, MigrationMode, RepoError, Repository, chain_id_of, migrations,
+        storage_id, stored_ids,
+    },
+    token::{MetadataAbi, NegativeReason, NegativeTokenResult, Token, TokenId, parse_token_id},
 };
 
-#[derive(Queryable, Insertable)]
-#[diesel(table_name = crate::schema::evm_tokens)]
+#[derive(Queryable, Insertable, AsChangeset)]
+#[diesel(table_name = crate::schema::evm_tokens, treat_none_as_null = true)]
 pub struct DbEvmToken {
     pub id: String,
     pub chain_id: i64,
     pub address: String,
-    pub symbol: String,
+    pub symbol: Option<String>,
     pub decimals: i32,
-    pub name: String,
+    pub name: Option<String>,
+    pub metadata_abi: String,
+    pub fetched_at: i64,
+    pub last_verified_at: i64,
+    pub source_block: Option<i64>,
+    pub refresh_attempted_at: Option<i64>,
+}
+
+#[derive(Queryable, Insertable, AsChangeset)]
+#[diesel(table_name = crate::schema::evm_token_negative_results)]
+pub struct DbEvmTokenNegativeResult {
+    pub id: String,
+    pub chain_id: i64,
+    pub address: String,
+    pub reason: String,
+    pub message: String,
+    pub recorded_at: i64,
 }
 
 #[derive(Clone)]
@@ -37,78 +56,447 @@ impl SqliteEvmTokenRepository {
 
         Self { pool }
     }
+
+    /// Runs blocking Diesel work `f` with a pooled connection on Tokio's blocking thread pool.
+    async fn with_connection<R, F>(&self, f: F) -> Result<R, RepoError>
+    where
+        R: Send + 'static,
+        F: FnOnce(&mut SqliteConnection) -> Result<R, RepoError> + Send + 'static,
+    {
+        let pool = self.pool.clone();
+        tokio::task::spawn_blocking(move || {
+            let mut connection = pool.get().map_err(|e| RepoError::Backend(e.to_string()))?;
+            f(&mut connection)
+        })
+        .await
+        .map_err(|e| RepoError::Backend(e.to_string()))?
+    }
 }
 
+#[async_trait]
 impl Repository<Token> for SqliteEvmTokenRepository {
-    fn get(&self, id: AccountId) -> Result<Option<Token>, RepoError> {
-        let mut connection: PooledConnection<ConnectionManager<SqliteConnection>> = self
-            .pool
-            .get()
-            .map_err(|e| RepoError::Backend(e.to_string()))?;
-
+    async fn get(&self, id: TokenId) -> Result<Option<Token>, RepoError> {
         debug!("Finding EVM token by id: {:?}", id.to_string());
 
-        let token: Option<DbEvmToken> = crate::schema::evm_tokens::table
-            .find(id.to_string())
-            .first::<DbEvmToken>(&mut connection)
-            .optional()?;
+        let token: Option<DbEvmToken> = self
+            .with_connection(move |connection| {
+                Ok(crate::schema::evm_tokens::table
+                    .filter(crate::schema::evm_tokens::id.eq_any(stored_ids(&id)))
+                    .first::<DbEvmToken>(connection)
+                    .optional()?)
+            })
+            .await?;
 
         match token {
-            Some(token) => {
-                let id: AccountId = token
-                    .id
-                    .parse::<AccountId>()
-                    .expect("Failed to create account id");
-
-                let token: Token = Token {
-                    id,
-                    symbol: token.symbol,
-                    decimals: token.decimals as u8,
-                    name: token.name,
-                };
-
-                return Ok(Some(token));
-            }
+            Some(token) => Ok(Some(Token::try_from(token)?)),
             None => {
-                debug!("Token not found by id: {:?}", id.to_string());
-                return Ok(None);
+                debug!("Token not found");
+                Ok(None)
             }
-        };
+        }
     }
 
-    fn save(&self, token: &Token) -> Result<(), RepoError> {
-        // Acquire a pooled connection for this operation
-        let mut connection: PooledConnection<ConnectionManager<SqliteConnection>> = self
-            .pool
-            .get()
-            .map_err(|e| RepoError::Backend(e.to_string()))?;
-
+    async fn save(&self, token: &Token) -> Result<(), RepoError> {
         use crate::schema::evm_tokens;
-        use diesel::prelude::*;
 
         info!("Saving EVM token with id: {:?}", token.id);
 
-        let chain_id: ChainId = token
-            .id
-            .chain_id()
-            .reference()
-            .to_string()
-            .parse::<ChainId>()
-            .map_err(|e| RepoError::Backend(format!("Failed to parse chain id: {}", e)))?;
-
-        let new_token: DbEvmToken = DbEvmToken {
-            id: token.id.to_string(),
-            chain_id,
-            address: token.id.address().to_string(),
+        let row = DbEvmToken::try_from(token)?;
+        self.with_connection(move |connection| {
+            // Concurrent or repeated fetches resolve the same token; the first stored copy wins.
+            diesel::insert_into(evm_tokens::table)
+                .values(&row)
+                .on_conflict(evm_tokens::id)
+                .do_nothing()
+                .execute(connection)?;
+            Ok(())
+        })
+        .await
+    }
+
+    async fn update(&self, token: &Token) -> Result<(), RepoError> {
+        use crate::schema::evm_tokens;
+
+        info!("Updating EVM token with id: {:?}", token.id);
+
+        let row = DbEvmToken::try_from(token)?;
+        let ids = stored_ids(&token.id);
+        let updated = self
+            .with_connection(move |connection| {
+                // A row still under its CAIP-10 id is updated in place.
+                for id in ids {
+                    let updated = diesel::update(evm_tokens::table.find(id))
+                        .set(&row)
+                        .execute(connection)?;
+                    if updated > 0 {
+                        return Ok(updated);
+                    }
+                }
+                Ok(0)
+            })
+            .await?;
+
+        if updated == 0 {
+            return Err(RepoError::NotFound);
+        }
+
+        Ok(())
+    }
+}
+
+#[async_trait]
+impl EvmTokenRepository for SqliteEvmTokenRepository {
+    async fn claim_stale(
+        &self,
+        cutoff: DateTime<Utc>,
+        retry_cutoff: DateTime<Utc>,
+        attempted_at: DateTime<Utc>,
+        limit: usize,
+    ) -> Result<Vec<Token>, RepoError> {
+        use crate::schema::evm_tokens;
+
+        let limit = i64::try_from(limit).unwrap_or(i64::MAX);
+        let rows = self
+            .with_connection(move |connection| {
+                // Takes the write lock up front, so concurrent claims see each other's marks.
+                Ok(connection.immediate_transaction(|connection| {
+                    let rows = evm_tokens::table
+                        .filter(evm_tokens::last_verified_at.lt(cutoff.timestamp()))
+                        .filter(
+                            evm_tokens::refresh_attempted_at
+                                .is_null()
+                                .or(evm_tokens::refresh_attempted_at.lt(retry_cutoff.timestamp())),
+                        )
+                        .order(evm_tokens::last_verified_at.asc())
+                        .limit(limit)
+                        .load::<DbEvmToken>(connection)?;
+                    let ids: Vec<&str> = rows.iter().map(|row| row.id.as_str()).collect();
+                    diesel::update(evm_tokens::table.filter(evm_tokens::id.eq_any(ids)))
+                        .set(evm_tokens::refresh_attempted_at.eq(attempted_at.timestamp()))
+                        .execute(connection)?;
+                    Ok::<_, diesel::result::Error>(rows)
+                })?)
+            })
+            .await?;
+
+        rows.into_iter().map(Token::try_from).collect()
+    }
+
+    async fn migrate(&self, mode: MigrationMode) -> Result<(), RepoError> {
+        self.with_connection(move |connection| migrations::run(connection, super::MIGRATIONS, mode))
+            .await
+    }
+}
+
+impl TryFrom<DbEvmToken> for Token {
+    type Error = RepoError;
+
+    fn try_from(token: DbEvmToken) -> Result<Self, Self::Error> {
+        let id = parse_token_id(&token.id)
+            .map_err(|e| RepoError::Backend(format!("Invalid token id {}: {e}", token.id)))?;
+
+        let metadata_abi = token
+            .metadata_abi
+            .parse::<MetadataAbi>()
+            .map_err(RepoError::Backend)?;
+
+        let mut result = Token::new(
+            id,
+            token.name,
+            token.symbol,
+            token.decimals as u8,
+            metadata_abi,
+        );
+        result.fetched_at = timestamp(token.fetched_at)?;
+        result.last_verified_at = timestamp(token.last_verified_at)?;
+        result.source_block = token.source_block.map(|block| block as u64);
+
+        Ok(result)
+    }
+}
+
+impl TryFrom<&Token> for DbEvmToken {
+    type Error = RepoError;
+
+    fn try_from(token: &Token) -> Result<Self, Self::Error> {
+        Ok(DbEvmToken {
+            id: storage_id(&token.id),
+            chain_id: chain_id_of(&token.id)?,
+            address: token.id.reference().to_string(),
             symbol: token.symbol.clone(),
             decimals: token.decimals as i32,
             name: token.name.clone(),
+            metadata_abi: token.metadata_abi.to_string(),
+            fetched_at: token.fetched_at.timestamp(),
+            last_verified_at: token.last_verified_at.timestamp(),
+            source_block: token.source_block.map(|block| block as i64),
+            refresh_attempted_at: None,
+        })
+    }
+}
+
+fn timestamp(seconds: i64) -> Result<DateTime<Utc>, RepoError> {
+    DateTime::from_timestamp(seconds, 0)
+        .ok_or_else(|| RepoError::Backend(format!("Invalid timestamp: {seconds}")))
+}
+
+#[async_trait]
+impl Repository<NegativeTokenResult> for SqliteEvmTokenRepository {
+    async fn get(&self, id: TokenId) -> Result<Option<NegativeTokenResult>, RepoError> {
+        debug!("Finding negative result by id: {:?}", id.to_string());
+
+        let keys = stored_ids(&id);
+        let result: Option<DbEvmTokenNegativeResult> = self
+            .with_connection(move |connection| {
+                Ok(crate::schema::evm_token_negative_results::table
+                    .filter(crate::schema::evm_token_negative_results::id.eq_any(keys))
+                    .first::<DbEvmTokenNegativeResult>(connection)
+                    .optional()?)
+            })
+            .await?;
+
+        let Some(result) = result else {
+            return Ok(None);
         };
 
-        diesel::insert_into(evm_tokens::table)
-            .values(&new_token)
-            .execute(&mut connection)?;
+        let reason = result
+            .reason
+            .parse::<NegativeReason>()
+            .map_err(RepoError::Backend)?;
+        let recorded_at = timestamp(result.recorded_at)?;
+
+        Ok(Some(NegativeTokenResult {
+            id,
+            reason,
+            message: result.message,
+            recorded_at,
+        }))
+    }
+
+    async fn save(&self, result: &NegativeTokenResult) -> Result<(), RepoError> {
+        use crate::schema::evm_token_negative_results;
+
+        info!(
+            "Saving negative result for {:?}: {}",
+            result.id.to_string(),
+            result.reason
+        );
+
+        let row = DbEvmTokenNegativeResult::try_from(result)?;
+        self.with_connection(move |connection| {
+            diesel::insert_into(evm_token_negative_results::table)
+                .values(&row)
+                .on_conflict(evm_token_negative_results::id)
+                .do_nothing()
+                .execute(connection)?;
+            Ok(())
+        })
+        .await
+    }
+
+    async fn update(&self, result: &NegativeTokenResult) -> Result<(), RepoError> {
+        use crate::schema::evm_token_negative_results;
+
+        let row = DbEvmTokenNegativeResult::try_from(result)?;
+        let ids = stored_ids(&result.id);
+        let updated = self
+            .with_connection(move |connection| {
+                for id in ids {
+                    let updated = diesel::update(evm_token_negative_results::table.find(id))
+                        .set(&row)
+                        .execute(connection)?;
+                    if updated > 0 {
+                        return Ok(updated);
+                    }
+                }
+                Ok(0)
+            })
+            .await?;
+
+        if updated == 0 {
+            return Err(RepoError::NotFound);
+        }
 
         Ok(())
     }
 }
+
+impl TryFrom<&NegativeTokenResult> for DbEvmTokenNegativeResult {
+    type Error = RepoError;
+
+    fn try_from(result: &NegativeTokenResult) -> Result<Self, Self::Error> {
+        Ok(DbEvmTokenNegativeResult {
+            id: storage_id(&result.id),
+            chain_id: chain_id_of(&result.id)?,
+            address: result.id.reference().to_string(),
+            reason: result.reason.to_string(),
+            message: result.message.clone(),
+            recorded_at: result.recorded_at.timestamp(),
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use diesel::connection::SimpleConnection;
+
+    use super::*;
+    use crate::repositories::{
+        conformance::repository_conformance_tests,
+        sqlite::test_support::{migrated_repository, unmigrated_repository},
+    };
+
+    repository_conformance_tests!(migrated_repository());
+
+    #[tokio::test]
+    async fn migrate_creates_the_schema_of_an_empty_database() {
+        let repository = unmigrated_repository();
+        repository.migrate(MigrationMode::VerifyOnly).await.unwrap();
+        let token = Token::new(
+            "eip155:1/erc20:0x000000000000000000000000000000000000dead"
+                .parse()
+                .unwrap(),
+            None,
+            Some("DEAD".to_string()),
+            18,
+            MetadataAbi::String,
+        );
+        assert!(repository.save(&token).await.is_err());
+
+        repository.migrate(MigrationMode::Apply).await.unwrap();
+        repository.save(&token).await.unwrap();
+        // Nothing left to apply.
+        repository.migrate(MigrationMode::Apply).await.unwrap();
+    }
+
+    #[tokio::test]
+    async fn migrate_refuses_a_schema_ahead_of_the_binary() {
+        let repository = migrated_repository();
+        repository
+            .with_connection(|connection| {
+                Ok(connection.batch_execute(
+                    "INSERT INTO __diesel_schema_migrations (version) VALUES ('29991231000000')",
+                )?)
+            })
+            .await
+            .unwrap();
+
+        for mode in [MigrationMode::Apply, MigrationMode::VerifyOnly] {
+            let err = repository.migrate(mode).await.expect_err("schema is ahead");
+            assert!(err.to_string().contains("29991231000000"), "{err}");
+        }
+    }
+
+    const DEAD: &str = "0x000000000000000000000000000000000000dead";
+
+    async fn insert_legacy_row(repository: &SqliteEvmTokenRepository) {
+        repository
+            .with_connection(|connection| {
+                Ok(connection.batch_execute(&format!(
+                    "INSERT INTO evm_tokens (id, chain_id, address, symbol, decimals) \
+                     VALUES ('eip155:1:{DEAD}', 1, '{DEAD}', 'DEAD', 18)"
+                ))?)
+            })
+            .await
+            .unwrap();
+    }
+
+    async fn stored_ids(repository: &SqliteEvmTokenRepository) -> Vec<String> {
+        repository
+            .with_connection(|connection| {
+                Ok(crate::schema::evm_tokens::table
+                    .select(crate::schema::evm_tokens::id)
+                    .load::<String>(connection)?)
+            })
+            .await
+            .unwrap()
+    }
+
+    #[tokio::test]
+    async fn rows_under_caip10_ids_are_read_and_updated() {
+        let repository = migrated_repository();
+        insert_legacy_row(&repository).await;
+        let id: TokenId = format!("eip155:1/erc20:{DEAD}").parse().unwrap();
+
+        let mut stored: Token = Repository::<Token>::get(&repository, id.clone())
+            .await
+            .unwrap()
+            .expect("legacy row is found by its CAIP-19 id");
+        assert_eq!(stored.id, id);
+        assert_eq!(stored.symbol.as_deref(), Some("DEAD"));
+
+        stored.symbol = Some("BEEF".to_string());
+        repository.update(&stored).await.unwrap();
+        let updated: Token = Repository::<Token>::get(&repository, id)
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(updated.symbol.as_deref(), Some("BEEF"));
+        assert_eq!(stored_ids(&repository).await.len(), 1);
+    }
+
+    #[tokio::test]
+    async fn saving_a_token_whose_caip10_row_exists_keeps_one_row() {
+        let repository = migrated_repository();
+        insert_legacy_row(&repository).await;
+
+        let token = Token::new(
+            format!("eip155:1/erc20:{DEAD}").parse().unwrap(),
+            None,
+            Some("BEEF".to_string()),
+            18,
+            MetadataAbi::String,
+        );
+        repository.save(&token).await.unwrap();
+
+        assert_eq!(stored_ids(&repository).await, [format!("eip155:1:{DEAD}")]);
+        let stored: Token = Repository::<Token>::get(&repository, token.id)
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(stored.symbol.as_deref(), Some("DEAD"));
+    }
+
+    #[tokio::test]
+    async fn tokens_are_written_under_caip10_ids_until_the_cutover() {
+        let repository = migrated_repository();
+        let id: TokenId = format!("eip155:1/erc20:{DEAD}").parse().unwrap();
+        repository
+            .save(&Token::new(
+                id.clone(),
+                None,
+                Some("DEAD".to_string()),
+                18,
+                MetadataAbi::String,
+            ))
+            .await
+            .unwrap();
+        repository
+            .save(&NegativeTokenResult {
+                id: format!("eip155:1/erc20:0x{}", "be".repeat(20))
+                    .parse()
+                    .unwrap(),
+                reason: NegativeReason::NotAContract,
+                message: "no code".to_string(),
+                recorded_at: Utc::now(),
+            })
+            .await
+            .unwrap();
+
+        assert_eq!(stored_ids(&repository).await, [format!("eip155:1:{DEAD}")]);
+        let negative_ids = repository
+            .with_connection(|connection| {
+                Ok(crate::schema::evm_token_negative_results::table
+                    .select(crate::schema::evm_token_negative_results::id)
+                    .load::<String>(connection)?)
+            })
+            .await
+            .unwrap();
+        assert_eq!(negative_ids, [format!("eip155:1:0x{}", "be".repeat(20))]);
+        let stored: Token = Repository::<Token>::get(&repository, id.clone())
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(stored.id, id);
+    }
+}
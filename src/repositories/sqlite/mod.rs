@@ -1 +1,9 @@
+use diesel_migrations::{EmbeddedMigrations, embed_migrations};
+
 pub mod evm_token;
+
+#[cfg(test)]
+pub(crate) mod test_support;
+
+/// The `migrations` directory, embedded at build time.
+pub const MIGRATIONS: EmbeddedMigrations = embed_migrations!("migrations");
@@ -0,0 +1,36 @@
+//! Throwaway SQLite databases for tests: shared-cache in-memory, unique per call, alive as long as
+//! the repository's pool holds a connection.
+
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+use diesel::prelude::*;
+use diesel_migrations::MigrationHarness;
+
+use super::{MIGRATIONS, evm_token::SqliteEvmTokenRepository};
+
+fn unique_memory_url() -> String {
+    static NEXT: AtomicUsize = AtomicUsize::new(0);
+    format!(
+        "file:token_api_test_{}_{}?mode=memory&cache=shared",
+        std::process::id(),
+        NEXT.fetch_add(1, Ordering::Relaxed)
+    )
+}
+
+/// Repository over an empty database without any tables, for storage-failure tests.
+pub(crate) fn unmigrated_repository() -> SqliteEvmTokenRepository {
+    SqliteEvmTokenRepository::new(unique_memory_url())
+}
+
+/// Repository over a database with all migrations applied.
+pub(crate) fn migrated_repository() -> SqliteEvmTokenRepository {
+    let url = unique_memory_url();
+    let repository = SqliteEvmTokenRepository::new(url.clone());
+
+    let mut connection = SqliteConnection::establish(&url).expect("open test database");
+    connection
+        .run_pending_migrations(MIGRATIONS)
+        .expect("apply test migrations");
+
+    repository
+}
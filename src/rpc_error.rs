@@ -0,0 +1,153 @@
+//! JSON-RPC error codes returned by `/rpc`. Codes are stable; `message` is human-readable and may
+//! change, `data` carries machine-readable details where noted.
+//!
+//! | Code     | Meaning                                              | `data`                                  |
+//! |----------|------------------------------------------------------|-----------------------------------------|
+//! | `-32602` | Invalid params (bad address, bad URL, batch too big) | `{ "field" }`                           |
+//! | `-32603` | Internal error                                       | —                                       |
+//! | `-32001` | Unknown chain: no RPC URLs for the chain id          | `{ "chain_id" }`                        |
+//! | `-32002` | RPC serves a different chain than requested          | `{ "expected_chain_id", "rpc_chain_id" }` |
+//! | `-32003` | Upstream (RPC node or Chainlist) unavailable         | `{ "source", "upstream_code"? }`        |
+//! | `-32004` | Upstream (RPC node or Chainlist) timed out           | `{ "source" }`                          |
+//! | `-32010` | Address has no contract code (EOA or undeployed)     | `{ "address" }`                         |
+//! | `-32011` | Contract does not expose ERC-20 metadata             | —                                       |
+//! | `-32020` | Token storage unavailable                            | —                                       |
+//!
+//! Native assets use `-32001` too when Chainlist has no `nativeCurrency` for the chain.
+
+use alloy::transports::TransportError;
+use serde::Serialize;
+use serde_json::{Value, json};
+
+use crate::{
+    repositories::RepoError,
+    services::{evm::error::EvmTokenServiceError, provider::ProviderServiceError},
+    types::ChainId,
+};
+
+pub const INVALID_PARAMS: i64 = -32602;
+pub const INTERNAL_ERROR: i64 = -32603;
+pub const UNKNOWN_CHAIN: i64 = -32001;
+pub const CHAIN_ID_MISMATCH: i64 = -32002;
+pub const UPSTREAM_UNAVAILABLE: i64 = -32003;
+pub const UPSTREAM_TIMEOUT: i64 = -32004;
+pub const NOT_A_CONTRACT: i64 = -32010;
+pub const NOT_A_TOKEN: i64 = -32011;
+pub const STORAGE_UNAVAILABLE: i64 = -32020;
+
+/// A JSON-RPC error object; also used for per-item errors in batch responses.
+#[derive(Debug, Clone, Serialize)]
+pub struct RpcError {
+    pub code: i64,
+    pub message: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub data: Option<Value>,
+}
+
+impl RpcError {
+    pub fn new(code: i64, message: impl Into<String>) -> Self {
+        Self {
+            code,
+            message: message.into(),
+            data: None,
+        }
+    }
+
+    pub fn with_data(mut self, data: Value) -> Self {
+        self.data = Some(data);
+        self
+    }
+
+    pub fn invalid_param(field: &str, message: impl Into<String>) -> Self {
+        Self::new(INVALID_PARAMS, message).with_data(json!({ "field": field }))
+    }
+
+    pub fn unknown_chain(chain_id: ChainId) -> Self {
+        Self::new(UNKNOWN_CHAIN, format!("No RPC URLs for chain {chain_id}"))
+            .with_data(json!({ "chain_id": chain_id }))
+    }
+
+    pub fn unknown_native_currency(chain_id: ChainId) -> Self {
+        Self::new(
+            UNKNOWN_CHAIN,
+            format!("No native currency metadata for chain {chain_id}"),
+        )
+        .with_data(json!({ "chain_id": chain_id }))
+    }
+}
+
+impl From<RpcError> for jsonrpc_v2::Error {
+    fn from(e: RpcError) -> Self {
+        jsonrpc_v2::Error::Full {
+            code: e.code,
+            message: e.message,
+            data: e.data.map(|d| Box::new(d) as _),
+        }
+    }
+}
+
+impl From<&EvmTokenServiceError> for RpcError {
+    fn from(e: &EvmTokenServiceError) -> Self {
+        let message = e.to_string();
+        match e {
+            EvmTokenServiceError::Repository(e) => RpcError::from(e),
+            EvmTokenServiceError::Chain(e) => transport_error(e, message),
+            EvmTokenServiceError::Multicall(_) => {
+                RpcError::new(UPSTREAM_UNAVAILABLE, message).with_data(json!({ "source": "rpc" }))
+            }
+            EvmTokenServiceError::InvalidMetadata(_) => RpcError::new(NOT_A_TOKEN, message),
+            EvmTokenServiceError::NotAContract(address) => RpcError::new(NOT_A_CONTRACT, message)
+                .with_data(json!({ "address": address.to_string() })),
+            EvmTokenServiceError::ChainIdMismatch(rpc_chain_id, expected_chain_id) => {
+                RpcError::new(CHAIN_ID_MISMATCH, message).with_data(json!({
+                    "expected_chain_id": expected_chain_id,
+                    "rpc_chain_id": rpc_chain_id,
+                }))
+            }
+            EvmTokenServiceError::CaipIdBuildFailed(_) => RpcError::new(INTERNAL_ERROR, message),
+            EvmTokenServiceError::Shared(e) => RpcError::from(e.as_ref()),
+        }
+    }
+}
+
+impl From<&ProviderServiceError> for RpcError {
+    fn from(e: &ProviderServiceError) -> Self {
+        let message = e.to_string();
+        match e {
+            ProviderServiceError::Chainlist(e) if e.is_timeout() => {
+                RpcError::new(UPSTREAM_TIMEOUT, message).with_data(json!({ "source": "chainlist" }))
+            }
+            ProviderServiceError::Chainlist(_) | ProviderServiceError::Url(_) => {
+                RpcError::new(UPSTREAM_UNAVAILABLE, message)
+                    .with_data(json!({ "source": "chainlist" }))
+            }
+            ProviderServiceError::Header(_) | ProviderServiceError::HttpClient(_) => {
+                RpcError::new(INTERNAL_ERROR, message)
+            }
+        }
+    }
+}
+
+impl From<&RepoError> for RpcError {
+    fn from(e: &RepoError) -> Self {
+        RpcError::new(STORAGE_UNAVAILABLE, e.to_string())
+    }
+}
+
+fn transport_error(e: &TransportError, message: String) -> RpcError {
+    let is_timeout = e
+        .as_transport_err()
+        .and_then(|kind| kind.as_custom())
+        .and_then(|err| err.downcast_ref::<reqwest::Error>())
+        .is_some_and(reqwest::Error::is_timeout);
+
+    if is_timeout {
+        return RpcError::new(UPSTREAM_TIMEOUT, message).with_data(json!({ "source": "rpc" }));
+    }
+
+    let data = match e.as_error_resp() {
+        Some(payload) => json!({ "source": "rpc", "upstream_code": payload.code }),
+        None => json!({ "source": "rpc" }),
+    };
+    RpcError::new(UPSTREAM_UNAVAILABLE, message).with_data(data)
+}
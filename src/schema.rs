@@ -5,8 +5,26 @@ diesel::table! {
         id -> Text,
         chain_id -> BigInt,
         address -> Text,
-        symbol -> Text,
+        symbol -> Nullable<Text>,
         decimals -> Integer,
-        name -> Text,
+        name -> Nullable<Text>,
+        metadata_abi -> Text,
+        fetched_at -> BigInt,
+        last_verified_at -> BigInt,
+        source_block -> Nullable<BigInt>,
+        refresh_attempted_at -> Nullable<BigInt>,
     }
 }
+
+diesel::table! {
+    evm_token_negative_results (id) {
+        id -> Text,
+        chain_id -> BigInt,
+        address -> Text,
+        reason -> Text,
+        message -> Text,
+        recorded_at -> BigInt,
+    }
+}
+
+diesel::allow_tables_to_appear_in_same_query!(evm_token_negative_results, evm_tokens,);
@@ -1,23 +1,49 @@
-use std::sync::Arc;
+use std::{
+    path::PathBuf,
+    sync::{Arc, Mutex},
+    time::{Duration, Instant},
+};
 
 use chrono::{DateTime, Duration as ChronoDuration, Utc};
+use log::{debug, info, warn};
 use tokio::sync::RwLock;
 
 use crate::{
-    chainlist::{CHAINLIST_API_URL, Chain, fetch_chains},
+    chainlist::{
+        CHAINLIST_API_URL, Chain, ChainlistError, ChainlistSource, bundled_chains, load_chains,
+        merge_chains,
+    },
     types::ChainId,
 };
 
+pub mod placeholders;
+pub mod policy;
+mod snapshot;
+
+pub use placeholders::RpcSecrets;
+pub use policy::{RpcPolicy, TrackingPolicy};
+
+/// Least time between attempts to refresh an expired list (shorter if the TTL is).
+const REFRESH_RETRY_DELAY: Duration = Duration::from_secs(60);
+
+/// Chainlist data cached for a TTL. Once expired, the last good list keeps being served while a
+/// background task fetches a new one; if Chainlist cannot be reached on first use, a snapshot on
+/// disk or bundled with the binary stands in.
 #[derive(Clone)]
 pub struct ChainlistService {
     inner: Arc<Inner>,
+    secrets: Arc<RpcSecrets>,
+    policy: RpcPolicy,
+    snapshot_path: Option<Arc<PathBuf>>,
+    bundled_snapshot: bool,
 }
 
 struct Inner {
     client: reqwest::Client,
-    chains_url: String,
+    sources: Vec<ChainlistSource>,
     ttl: ChronoDuration,
     cache: RwLock<Option<CacheEntry>>,
+    refresh: Mutex<RefreshState>,
 }
 
 struct CacheEntry {
@@ -25,6 +51,12 @@ struct CacheEntry {
     fetched_at: DateTime<Utc>,
 }
 
+#[derive(Default)]
+struct RefreshState {
+    running: bool,
+    last_attempt: Option<Instant>,
+}
+
 impl ChainlistService {
     pub fn new(ttl: std::time::Duration) -> Self {
         Self::with_client_and_url(ttl, reqwest::Client::new(), CHAINLIST_API_URL)
@@ -35,63 +67,235 @@ impl ChainlistService {
         client: reqwest::Client,
         chains_url: impl Into<String>,
     ) -> Self {
+        Self::with_client_and_sources(ttl, client, vec![ChainlistSource::Url(chains_url.into())])
+    }
+
+    /// Chains merged from `sources` in priority order (see [`merge_chains`]); a source that fails
+    /// to load is left out, and a fetch fails only if every source does.
+    pub fn with_client_and_sources(
+        ttl: std::time::Duration,
+        client: reqwest::Client,
+        sources: Vec<ChainlistSource>,
+    ) -> Self {
+        assert!(!sources.is_empty(), "at least one Chainlist source");
         let ttl = ChronoDuration::from_std(ttl).expect("TTL must fit in chrono::Duration");
         Self {
             inner: Arc::new(Inner {
                 client,
-                chains_url: chains_url.into(),
+                sources,
                 ttl,
                 cache: RwLock::new(None),
+                refresh: Mutex::new(RefreshState::default()),
             }),
+            secrets: Arc::new(RpcSecrets::default()),
+            policy: RpcPolicy::default(),
+            snapshot_path: None,
+            bundled_snapshot: false,
         }
     }
 
-    pub async fn chains(&self) -> Result<Vec<Chain>, reqwest::Error> {
+    /// Secrets for `${NAME}` placeholders in RPC URLs; URLs needing any other secret are dropped.
+    pub fn with_rpc_secrets(mut self, secrets: RpcSecrets) -> Self {
+        self.secrets = Arc::new(secrets);
+        self
+    }
+
+    /// Which RPCs [`Self::rpc_urls_for_chain`] returns; by default every HTTP(S) RPC.
+    pub fn with_rpc_policy(mut self, policy: RpcPolicy) -> Self {
+        self.policy = policy;
+        self
+    }
+
+    /// Writes every fetched list to `path` and reads it back when Chainlist fails on first use.
+    pub fn with_snapshot_path(mut self, path: impl Into<PathBuf>) -> Self {
+        self.snapshot_path = Some(Arc::new(path.into()));
+        self
+    }
+
+    /// Falls back to the chains bundled with the binary when neither Chainlist nor the snapshot
+    /// on disk is available on first use.
+    pub fn with_bundled_snapshot(mut self, enabled: bool) -> Self {
+        self.bundled_snapshot = enabled;
+        self
+    }
+
+    pub async fn chains(&self) -> Result<Vec<Chain>, ChainlistError> {
         self.chains_shared().await.map(|v| (*v).clone())
     }
 
-    pub async fn chains_shared(&self) -> Result<Arc<Vec<Chain>>, reqwest::Error> {
+    /// The cached list, fetched on first use. An expired list is returned as is and refreshed in
+    /// the background, so only a first fetch without any snapshot to fall back on can fail.
+    pub async fn chains_shared(&self) -> Result<Arc<Vec<Chain>>, ChainlistError> {
         let inner = self.inner.as_ref();
 
         {
             let guard = inner.cache.read().await;
             if let Some(entry) = guard.as_ref() {
-                if Self::is_fresh(entry, inner.ttl) {
-                    return Ok(Arc::clone(&entry.chains));
-                }
+                return Ok(self.serve(entry));
             }
         }
 
         let mut guard = inner.cache.write().await;
         if let Some(entry) = guard.as_ref() {
-            if Self::is_fresh(entry, inner.ttl) {
-                return Ok(Arc::clone(&entry.chains));
+            return Ok(self.serve(entry));
+        }
+
+        inner.refresh.lock().unwrap().last_attempt = Some(Instant::now());
+        let entry = match self.load().await {
+            Ok(list) => {
+                let chains = Arc::new(list);
+                self.persist(&chains);
+                CacheEntry {
+                    chains,
+                    fetched_at: Utc::now(),
+                }
+            }
+            Err(e) => {
+                let Some((chains, written)) = self.load_snapshot() else {
+                    return Err(e);
+                };
+                warn!(
+                    "Chainlist unavailable ({e}); serving {} chains from a snapshot of {written}",
+                    chains.len()
+                );
+                // Expired from the start, so the next use after the retry delay tries Chainlist.
+                CacheEntry {
+                    chains: Arc::new(chains),
+                    fetched_at: DateTime::UNIX_EPOCH,
+                }
+            }
+        };
+        let chains = Arc::clone(&entry.chains);
+        *guard = Some(entry);
+        Ok(chains)
+    }
+
+    /// `entry`'s chains, starting a background refresh if they have expired.
+    fn serve(&self, entry: &CacheEntry) -> Arc<Vec<Chain>> {
+        if !Self::is_fresh(entry, self.inner.ttl) {
+            self.revalidate_in_background();
+        }
+        Arc::clone(&entry.chains)
+    }
+
+    /// Fetches the list on a background task unless one is running or the last attempt was less
+    /// than [`REFRESH_RETRY_DELAY`] ago. On failure the current list stays.
+    fn revalidate_in_background(&self) {
+        {
+            let retry_delay = self
+                .inner
+                .ttl
+                .to_std()
+                .map_or(REFRESH_RETRY_DELAY, |ttl| ttl.min(REFRESH_RETRY_DELAY));
+            let mut state = self.inner.refresh.lock().unwrap();
+            if state.running
+                || state
+                    .last_attempt
+                    .is_some_and(|attempt| attempt.elapsed() < retry_delay)
+            {
+                return;
             }
+            state.running = true;
+            state.last_attempt = Some(Instant::now());
         }
 
-        let list = fetch_chains(&inner.client, inner.chains_url.as_str()).await?;
-        let chains = Arc::new(list);
-        *guard = Some(CacheEntry {
-            chains: Arc::clone(&chains),
-            fetched_at: Utc::now(),
+        let service = self.clone();
+        tokio::spawn(async move {
+            let inner = service.inner.as_ref();
+            match service.load().await {
+                Ok(list) => {
+                    let chains = Arc::new(list);
+                    service.persist(&chains);
+                    debug!("Refreshed Chainlist data: {} chains", chains.len());
+                    *inner.cache.write().await = Some(CacheEntry {
+                        chains,
+                        fetched_at: Utc::now(),
+                    });
+                }
+                Err(e) => warn!("Chainlist refresh failed, keeping the current list: {e}"),
+            }
+            inner.refresh.lock().unwrap().running = false;
         });
-        Ok(chains)
     }
 
-    pub async fn get_chain_data(&self, chain_id: ChainId) -> Result<Option<Chain>, reqwest::Error> {
+    /// Merges the sources that load; fails only if none does.
+    async fn load(&self) -> Result<Vec<Chain>, ChainlistError> {
+        let mut lists = Vec::with_capacity(self.inner.sources.len());
+        let mut last_error = None;
+        for source in &self.inner.sources {
+            match load_chains(&self.inner.client, source).await {
+                Ok(chains) => {
+                    debug!("Loaded {} chains from {source}", chains.len());
+                    lists.push(chains);
+                }
+                Err(e) => {
+                    warn!("Skipping Chainlist source {source}: {e}");
+                    last_error = Some(e);
+                }
+            }
+        }
+        match last_error {
+            Some(e) if lists.is_empty() => Err(e),
+            _ => Ok(merge_chains(lists)),
+        }
+    }
+
+    fn persist(&self, chains: &Arc<Vec<Chain>>) {
+        if let Some(path) = &self.snapshot_path {
+            snapshot::write_in_background(path.as_ref().clone(), Arc::clone(chains));
+        }
+    }
+
+    /// Chains from the snapshot on disk, else the bundled ones (if enabled), and when they were
+    /// written.
+    fn load_snapshot(&self) -> Option<(Vec<Chain>, DateTime<Utc>)> {
+        self.snapshot_path
+            .as_deref()
+            .and_then(|path| snapshot::read(path))
+            .or_else(|| {
+                self.bundled_snapshot.then(|| {
+                    info!("Using the Chainlist snapshot bundled with the binary");
+                    (bundled_chains(), DateTime::UNIX_EPOCH)
+                })
+            })
+    }
+
+    pub async fn get_chain_data(&self, chain_id: ChainId) -> Result<Option<Chain>, ChainlistError> {
         let chains = self.chains_shared().await?;
         Ok(chains.iter().find(|c| c.chain_id == chain_id).cloned())
     }
 
-    /// Trimmed, non-empty RPC URLs from Chainlist for `chain_id` (no liveness checks).
+    /// [`Self::usable_rpc_urls`] of chain `chain_id`; `None` if Chainlist does not list it.
     pub async fn rpc_urls_for_chain(
         &self,
         chain_id: ChainId,
-    ) -> Result<Option<Vec<String>>, reqwest::Error> {
+    ) -> Result<Option<Vec<String>>, ChainlistError> {
         let Some(chain) = self.get_chain_data(chain_id).await? else {
             return Ok(None);
         };
-        Ok(Some(trimmed_rpc_urls(chain)))
+        let urls = self.usable_rpc_urls(&chain);
+        if urls.len() < chain.rpc.len() {
+            debug!(
+                "Using {} of {} RPC URLs for chain {chain_id} (the rest are dropped by policy or scheme, or have unresolved placeholders)",
+                urls.len(),
+                chain.rpc.len()
+            );
+        }
+        Ok(Some(urls))
+    }
+
+    /// Trimmed, non-empty HTTP(S) RPC URLs of `chain` that the [`RpcPolicy`] allows, with
+    /// `${NAME}` placeholders expanded; URLs with unresolvable placeholders are left out (no
+    /// liveness checks).
+    pub fn usable_rpc_urls(&self, chain: &Chain) -> Vec<String> {
+        chain
+            .rpc
+            .iter()
+            .filter(|rpc| self.policy.allows(rpc))
+            .map(|rpc| rpc.url.trim())
+            .filter(|url| !url.is_empty())
+            .filter_map(|url| self.secrets.expand(url))
+            .collect()
     }
 
     fn is_fresh(entry: &CacheEntry, ttl: ChronoDuration) -> bool {
@@ -99,15 +303,6 @@ impl ChainlistService {
     }
 }
 
-fn trimmed_rpc_urls(chain: Chain) -> Vec<String> {
-    chain
-        .rpc
-        .into_iter()
-        .map(|r| r.url.trim().to_owned())
-        .filter(|u| !u.is_empty())
-        .collect()
-}
-
 #[cfg(test)]
 mod tests {
     use std::time::Duration;
@@ -151,8 +346,29 @@ mod tests {
         assert!(Arc::ptr_eq(&a, &b));
     }
 
+    /// Polls `chains_shared` until it returns a list other than `stale`.
+    async fn refreshed(svc: &ChainlistService, stale: &Arc<Vec<Chain>>) -> Arc<Vec<Chain>> {
+        for _ in 0..100 {
+            let chains = svc.chains_shared().await.unwrap();
+            if !Arc::ptr_eq(&chains, stale) {
+                return chains;
+            }
+            tokio::time::sleep(Duration::from_millis(10)).await;
+        }
+        panic!("chain list was not refreshed");
+    }
+
+    fn snapshot_path(name: &str) -> PathBuf {
+        let path = std::env::temp_dir().join(format!(
+            "token_api_chainlist_{name}_{}.json",
+            std::process::id()
+        ));
+        let _ = std::fs::remove_file(&path);
+        path
+    }
+
     #[tokio::test]
-    async fn chains_shared_refetches_after_ttl() {
+    async fn chains_shared_serves_expired_list_while_refetching_in_background() {
         let server = MockServer::start().await;
         let url = format!("{}/rpcs.json", server.uri());
         Mock::given(method("GET"))
@@ -167,9 +383,181 @@ mod tests {
             reqwest::Client::new(),
             url,
         );
-        svc.chains_shared().await.unwrap();
+        let first = svc.chains_shared().await.unwrap();
         tokio::time::sleep(Duration::from_millis(50)).await;
+        let stale = svc.chains_shared().await.unwrap();
+        assert!(Arc::ptr_eq(&first, &stale));
+        refreshed(&svc, &first).await;
+    }
+
+    #[tokio::test]
+    async fn chains_shared_keeps_last_good_list_when_refresh_fails() {
+        let server = MockServer::start().await;
+        let url = format!("{}/rpcs.json", server.uri());
+        Mock::given(method("GET"))
+            .and(path("/rpcs.json"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(sample_chain_json()))
+            .up_to_n_times(1)
+            .mount(&server)
+            .await;
+        Mock::given(method("GET"))
+            .and(path("/rpcs.json"))
+            .respond_with(ResponseTemplate::new(503))
+            .mount(&server)
+            .await;
+
+        let svc = ChainlistService::with_client_and_url(
+            Duration::from_millis(20),
+            reqwest::Client::new(),
+            url,
+        );
         svc.chains_shared().await.unwrap();
+        for _ in 0..5 {
+            tokio::time::sleep(Duration::from_millis(30)).await;
+            let chain = svc.get_chain_data(42).await.unwrap();
+            assert_eq!(chain.expect("chain 42").name, "Test Net");
+        }
+        assert!(server.received_requests().await.unwrap().len() > 1);
+    }
+
+    #[tokio::test]
+    async fn first_fetch_failure_falls_back_to_snapshot_on_disk() {
+        let path = snapshot_path("disk");
+
+        let up = MockServer::start().await;
+        Mock::given(method("GET"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(sample_chain_json()))
+            .mount(&up)
+            .await;
+        ChainlistService::with_client_and_url(
+            Duration::from_secs(3600),
+            reqwest::Client::new(),
+            up.uri(),
+        )
+        .with_snapshot_path(&path)
+        .chains_shared()
+        .await
+        .unwrap();
+        for _ in 0..100 {
+            if path.exists() {
+                break;
+            }
+            tokio::time::sleep(Duration::from_millis(10)).await;
+        }
+
+        let down = MockServer::start().await;
+        Mock::given(method("GET"))
+            .respond_with(ResponseTemplate::new(503))
+            .mount(&down)
+            .await;
+        let svc = ChainlistService::with_client_and_url(
+            Duration::from_secs(3600),
+            reqwest::Client::new(),
+            down.uri(),
+        );
+        assert!(svc.chains_shared().await.is_err());
+
+        let svc = svc.with_snapshot_path(&path);
+        let chain = svc.get_chain_data(42).await.unwrap().expect("chain 42");
+        assert_eq!(chain.name, "Test Net");
+        std::fs::remove_file(&path).unwrap();
+    }
+
+    #[tokio::test]
+    async fn first_fetch_failure_falls_back_to_bundled_snapshot() {
+        let down = MockServer::start().await;
+        Mock::given(method("GET"))
+            .respond_with(ResponseTemplate::new(503))
+            .mount(&down)
+            .await;
+
+        let svc = ChainlistService::with_client_and_url(
+            Duration::from_secs(3600),
+            reqwest::Client::new(),
+            down.uri(),
+        )
+        .with_snapshot_path(snapshot_path("missing"))
+        .with_bundled_snapshot(true);
+        let mainnet = svc.get_chain_data(1).await.unwrap().expect("mainnet");
+        assert_eq!(mainnet.name, "Ethereum Mainnet");
+        assert!(!svc.rpc_urls_for_chain(1).await.unwrap().unwrap().is_empty());
+    }
+
+    #[tokio::test]
+    async fn sources_merge_in_priority_order() {
+        let curated = snapshot_path("curated");
+        std::fs::write(
+            &curated,
+            json!([{
+                "name": "Curated Net",
+                "chain": "TEST",
+                "chainId": 42,
+                "rpc": [{ "url": "https://private.test" }, { "url": "https://rpc.test" }],
+            }])
+            .to_string(),
+        )
+        .unwrap();
+
+        let server = MockServer::start().await;
+        Mock::given(method("GET"))
+            .and(path("/rpcs.json"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(sample_chain_json()))
+            .mount(&server)
+            .await;
+
+        let svc = ChainlistService::with_client_and_sources(
+            Duration::from_secs(3600),
+            reqwest::Client::new(),
+            vec![
+                ChainlistSource::File(curated.clone()),
+                ChainlistSource::Url(format!("{}/rpcs.json", server.uri())),
+            ],
+        );
+        let chain = svc.get_chain_data(42).await.unwrap().expect("chain 42");
+        assert_eq!(chain.name, "Curated Net");
+        let urls = svc.rpc_urls_for_chain(42).await.unwrap().expect("urls");
+        assert_eq!(urls, ["https://private.test", "https://rpc.test"]);
+
+        std::fs::remove_file(&curated).unwrap();
+    }
+
+    #[tokio::test]
+    async fn unreachable_source_is_skipped_while_others_load() {
+        let curated = snapshot_path("curated-only");
+        std::fs::write(
+            &curated,
+            json!([{
+                "name": "Curated Net",
+                "chain": "TEST",
+                "chainId": 42,
+                "rpc": [{ "url": "https://private.test" }],
+            }])
+            .to_string(),
+        )
+        .unwrap();
+
+        let svc = ChainlistService::with_client_and_sources(
+            Duration::from_secs(3600),
+            reqwest::Client::new(),
+            vec![
+                ChainlistSource::File(curated.clone()),
+                ChainlistSource::Url("http://127.0.0.1:1/rpcs.json".to_string()),
+            ],
+        );
+        let urls = svc.rpc_urls_for_chain(42).await.unwrap().expect("chain 42");
+        assert_eq!(urls, ["https://private.test"]);
+
+        std::fs::remove_file(&curated).unwrap();
+
+        let unreachable = ChainlistService::with_client_and_sources(
+            Duration::from_secs(3600),
+            reqwest::Client::new(),
+            vec![
+                ChainlistSource::File(curated),
+                ChainlistSource::Url("http://127.0.0.1:1/rpcs.json".to_string()),
+            ],
+        );
+        assert!(unreachable.chains_shared().await.is_err());
     }
 
     #[tokio::test]
@@ -242,6 +630,93 @@ mod tests {
         );
     }
 
+    #[tokio::test]
+    async fn rpc_urls_for_chain_expands_placeholders_and_drops_unresolved() {
+        let server = MockServer::start().await;
+        let chain_json = json!([{
+            "name": "Keys",
+            "chain": "KEY",
+            "chainId": 1,
+            "rpc": [
+                { "url": "https://mainnet.infura.io/v3/${INFURA_API_KEY}" },
+                { "url": "https://eth-mainnet.alchemyapi.io/v2/${ALCHEMY_API_KEY}" },
+                { "url": "https://public.test" },
+            ],
+        }]);
+        let url = format!("{}/rpcs.json", server.uri());
+        Mock::given(method("GET"))
+            .and(path("/rpcs.json"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(chain_json))
+            .mount(&server)
+            .await;
+
+        let svc = ChainlistService::with_client_and_url(
+            Duration::from_secs(3600),
+            reqwest::Client::new(),
+            url,
+        );
+        let urls = svc.rpc_urls_for_chain(1).await.unwrap().expect("urls");
+        assert_eq!(urls, vec!["https://public.test".to_string()]);
+
+        let svc = svc.with_rpc_secrets(RpcSecrets::new(
+            [("INFURA_API_KEY".to_string(), "abc123".to_string())].into(),
+        ));
+        let urls = svc.rpc_urls_for_chain(1).await.unwrap().expect("urls");
+        assert_eq!(
+            urls,
+            vec![
+                "https://mainnet.infura.io/v3/abc123".to_string(),
+                "https://public.test".to_string()
+            ]
+        );
+    }
+
+    #[tokio::test]
+    async fn rpc_urls_for_chain_applies_scheme_and_tracking_policy() {
+        let server = MockServer::start().await;
+        let chain_json = json!([{
+            "name": "Policy",
+            "chain": "POL",
+            "chainId": 42,
+            "rpc": [
+                { "url": "wss://ws.test", "tracking": "none" },
+                { "url": "https://none.test", "tracking": "none", "isOpenSource": true },
+                { "url": "https://limited.test", "tracking": "limited" },
+                { "url": "https://yes.test", "tracking": "yes" },
+                { "url": "https://undeclared.test" },
+            ],
+        }]);
+        let url = format!("{}/rpcs.json", server.uri());
+        Mock::given(method("GET"))
+            .and(path("/rpcs.json"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(chain_json))
+            .mount(&server)
+            .await;
+
+        let svc = ChainlistService::with_client_and_url(
+            Duration::from_secs(3600),
+            reqwest::Client::new(),
+            url,
+        );
+        let urls = svc.rpc_urls_for_chain(42).await.unwrap().expect("urls");
+        assert_eq!(
+            urls,
+            [
+                "https://none.test",
+                "https://limited.test",
+                "https://yes.test",
+                "https://undeclared.test"
+            ]
+        );
+
+        let svc = svc.with_rpc_policy(RpcPolicy {
+            tracking: TrackingPolicy::None,
+            open_source_only: false,
+        });
+        let urls = svc.rpc_urls_for_chain(42).await.unwrap().expect("urls");
+        assert_eq!(urls, ["https://none.test"]);
+    }
+
     #[tokio::test]
     async fn rpc_urls_for_chain_none_when_missing_chain() {
         let server = MockServer::start().await;
@@ -0,0 +1,98 @@
+//! `${NAME}` placeholders in Chainlist RPC URLs, e.g.
+//! `https://mainnet.infura.io/v3/${INFURA_API_KEY}`, filled in from configured secrets.
+
+use std::{collections::HashMap, fmt};
+
+use serde::Deserialize;
+
+/// API keys by placeholder name. `Debug` shows the names only.
+#[derive(Clone, Default, PartialEq, Eq, Deserialize)]
+#[serde(transparent)]
+pub struct RpcSecrets(HashMap<String, String>);
+
+impl RpcSecrets {
+    pub fn new(secrets: HashMap<String, String>) -> Self {
+        Self(secrets)
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+
+    /// `url` with every `${NAME}` replaced by secret `NAME`; `None` if a placeholder names an
+    /// unknown secret or is not closed.
+    pub fn expand(&self, url: &str) -> Option<String> {
+        let mut expanded = String::with_capacity(url.len());
+        let mut rest = url;
+        while let Some(start) = rest.find("${") {
+            expanded.push_str(&rest[..start]);
+            let after = &rest[start + 2..];
+            let end = after.find('}')?;
+            expanded.push_str(self.0.get(&after[..end])?);
+            rest = &after[end + 1..];
+        }
+        expanded.push_str(rest);
+        Some(expanded)
+    }
+}
+
+impl fmt::Debug for RpcSecrets {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let mut names: Vec<&String> = self.0.keys().collect();
+        names.sort();
+        f.debug_tuple("RpcSecrets").field(&names).finish()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn secrets() -> RpcSecrets {
+        RpcSecrets::new(HashMap::from([
+            ("INFURA_API_KEY".to_string(), "infura-key".to_string()),
+            ("ALCHEMY_API_KEY".to_string(), "alchemy-key".to_string()),
+        ]))
+    }
+
+    #[test]
+    fn expand_substitutes_known_placeholders() {
+        let secrets = secrets();
+        assert_eq!(
+            secrets
+                .expand("https://mainnet.infura.io/v3/${INFURA_API_KEY}")
+                .as_deref(),
+            Some("https://mainnet.infura.io/v3/infura-key")
+        );
+        assert_eq!(
+            secrets
+                .expand("https://${ALCHEMY_API_KEY}.example/${INFURA_API_KEY}/x")
+                .as_deref(),
+            Some("https://alchemy-key.example/infura-key/x")
+        );
+        assert_eq!(
+            secrets.expand("https://rpc.example").as_deref(),
+            Some("https://rpc.example")
+        );
+    }
+
+    #[test]
+    fn expand_rejects_unknown_or_unclosed_placeholders() {
+        let secrets = secrets();
+        assert_eq!(secrets.expand("https://rpc.example/${ANKR_API_KEY}"), None);
+        assert_eq!(secrets.expand("https://rpc.example/${INFURA_API_KEY"), None);
+        assert_eq!(
+            RpcSecrets::default().expand("https://mainnet.infura.io/v3/${INFURA_API_KEY}"),
+            None
+        );
+    }
+
+    #[test]
+    fn debug_lists_names_without_values() {
+        let debug = format!("{:?}", secrets());
+        assert_eq!(
+            debug,
+            r#"RpcSecrets(["ALCHEMY_API_KEY", "INFURA_API_KEY"])"#
+        );
+    }
+}
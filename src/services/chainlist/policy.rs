@@ -0,0 +1,143 @@
+//! Which Chainlist RPCs are used: only HTTP(S) URLs, which Alloy's `Http` transport can call,
+//! filtered by what Chainlist says about their tracking and source code.
+
+use std::{fmt, str::FromStr};
+
+use serde::Deserialize;
+use url::Url;
+
+use crate::chainlist::{Rpc, Tracking};
+
+/// Most tracking an RPC may declare to be used. RPCs without a declaration count as tracking.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum TrackingPolicy {
+    /// Only RPCs declaring `"tracking": "none"`.
+    None,
+    /// `"none"` or `"limited"`.
+    Limited,
+    /// Every RPC.
+    #[default]
+    Any,
+}
+
+impl FromStr for TrackingPolicy {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "none" => Ok(Self::None),
+            "limited" => Ok(Self::Limited),
+            "any" => Ok(Self::Any),
+            _ => Err("expected none, limited or any".to_string()),
+        }
+    }
+}
+
+impl fmt::Display for TrackingPolicy {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            Self::None => "none",
+            Self::Limited => "limited",
+            Self::Any => "any",
+        })
+    }
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct RpcPolicy {
+    pub tracking: TrackingPolicy,
+    /// Only RPCs Chainlist marks `"isOpenSource": true`.
+    pub open_source_only: bool,
+}
+
+impl RpcPolicy {
+    pub fn allows(&self, rpc: &Rpc) -> bool {
+        supported_scheme(&rpc.url) && self.allows_tracking(rpc.tracking) && self.allows_source(rpc)
+    }
+
+    fn allows_tracking(&self, tracking: Option<Tracking>) -> bool {
+        match self.tracking {
+            TrackingPolicy::Any => true,
+            TrackingPolicy::Limited => tracking.is_some_and(|t| t <= Tracking::Limited),
+            TrackingPolicy::None => tracking == Some(Tracking::None),
+        }
+    }
+
+    fn allows_source(&self, rpc: &Rpc) -> bool {
+        !self.open_source_only || rpc.is_open_source == Some(true)
+    }
+}
+
+/// `http` and `https` only. Unparseable URLs are kept so they are reported when the client is
+/// built rather than silently ignored.
+fn supported_scheme(url: &str) -> bool {
+    match Url::parse(url.trim()) {
+        Ok(url) => matches!(url.scheme(), "http" | "https"),
+        Err(_) => true,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn rpc(url: &str, tracking: Option<Tracking>, is_open_source: Option<bool>) -> Rpc {
+        Rpc {
+            url: url.to_string(),
+            tracking,
+            is_open_source,
+        }
+    }
+
+    #[test]
+    fn only_http_schemes_are_allowed() {
+        let policy = RpcPolicy::default();
+        assert!(policy.allows(&rpc("https://rpc.test", None, None)));
+        assert!(policy.allows(&rpc(" http://rpc.test ", None, None)));
+        assert!(!policy.allows(&rpc("wss://rpc.test", None, None)));
+        assert!(!policy.allows(&rpc("ws://rpc.test", None, None)));
+    }
+
+    #[test]
+    fn tracking_policy_admits_declared_levels_up_to_its_own() {
+        let levels = [
+            Some(Tracking::None),
+            Some(Tracking::Limited),
+            Some(Tracking::Yes),
+            Some(Tracking::Unknown),
+            None,
+        ];
+        let allowed = |tracking: TrackingPolicy| -> Vec<bool> {
+            let policy = RpcPolicy {
+                tracking,
+                open_source_only: false,
+            };
+            levels
+                .iter()
+                .map(|level| policy.allows(&rpc("https://rpc.test", *level, None)))
+                .collect()
+        };
+
+        assert_eq!(allowed(TrackingPolicy::Any), [true, true, true, true, true]);
+        assert_eq!(
+            allowed(TrackingPolicy::Limited),
+            [true, true, false, false, false]
+        );
+        assert_eq!(
+            allowed(TrackingPolicy::None),
+            [true, false, false, false, false]
+        );
+    }
+
+    #[test]
+    fn open_source_only_requires_the_flag() {
+        let policy = RpcPolicy {
+            tracking: TrackingPolicy::Any,
+            open_source_only: true,
+        };
+        assert!(policy.allows(&rpc("https://rpc.test", None, Some(true))));
+        assert!(!policy.allows(&rpc("https://rpc.test", None, Some(false))));
+        assert!(!policy.allows(&rpc("https://rpc.test", None, None)));
+    }
+}
@@ -0,0 +1,49 @@
+//! The last Chainlist response that parsed, kept on disk for starting while Chainlist is down.
+
+use std::{
+    fs,
+    path::{Path, PathBuf},
+};
+
+use chrono::{DateTime, Utc};
+use log::{debug, warn};
+
+use crate::chainlist::Chain;
+
+/// Chains from the snapshot at `path` and when it was written; `None` if it is missing or does not
+/// parse.
+pub(super) fn read(path: &Path) -> Option<(Vec<Chain>, DateTime<Utc>)> {
+    let contents = match fs::read(path) {
+        Ok(contents) => contents,
+        Err(e) => {
+            debug!("No Chainlist snapshot at {}: {e}", path.display());
+            return None;
+        }
+    };
+    let chains = serde_json::from_slice(&contents)
+        .inspect_err(|e| warn!("Ignoring Chainlist snapshot {}: {e}", path.display()))
+        .ok()?;
+    let written = fs::metadata(path)
+        .and_then(|metadata| metadata.modified())
+        .map_or(DateTime::UNIX_EPOCH, DateTime::from);
+    Some((chains, written))
+}
+
+/// Replaces the snapshot at `path` with `chains` on the blocking pool; failures are logged.
+pub(super) fn write_in_background(path: PathBuf, chains: std::sync::Arc<Vec<Chain>>) {
+    tokio::task::spawn_blocking(move || {
+        let tmp = path.with_extension("tmp");
+        let written = serde_json::to_vec(chains.as_ref())
+            .map_err(std::io::Error::other)
+            .and_then(|json| fs::write(&tmp, json))
+            .and_then(|()| fs::rename(&tmp, &path));
+        match written {
+            Ok(()) => debug!(
+                "Wrote {} chains to Chainlist snapshot {}",
+                chains.len(),
+                path.display()
+            ),
+            Err(e) => warn!("Failed to write Chainlist snapshot {}: {e}", path.display()),
+        }
+    });
+}
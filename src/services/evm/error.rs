@@ -1,6 +1,7 @@
+use std::sync::Arc;
+
 use crate::repositories::RepoError;
-use actix_web::error::BlockingError;
-use alloy::transports::TransportError;
+use alloy::{primitives::Address, transports::TransportError};
 use thiserror::Error;
 
 #[derive(Debug, Error)]
@@ -11,17 +12,61 @@ pub enum EvmTokenServiceError {
     #[error("Chain error: {0}")]
     Chain(TransportError),
 
+    /// The aggregate call itself failed or returned malformed data.
     #[error("Multicall error: {0}")]
     Multicall(String),
 
+    /// The contract answered, but not with usable ERC-20 metadata.
+    #[error("Invalid token metadata: {0}")]
+    InvalidMetadata(String),
+
+    #[error("Address {0} is not a contract")]
+    NotAContract(Address),
+
     #[error("Chain ID mismatch: {0} != {1}")]
     ChainIdMismatch(u64, u64),
 
     #[error("CAIP ID build failed: {0}")]
     CaipIdBuildFailed(tap_caip::error::Error),
 
-    #[error("Blocking error: {0}")]
-    BlockingError(BlockingError),
+    /// A failure of a lookup shared by several concurrent callers.
+    #[error(transparent)]
+    Shared(Arc<EvmTokenServiceError>),
+}
+
+impl EvmTokenServiceError {
+    /// Failures that repeat for the same address no matter how often it is fetched (as opposed to
+    /// transport, RPC or storage failures).
+    pub fn is_deterministic(&self) -> bool {
+        match self {
+            EvmTokenServiceError::NotAContract(_) | EvmTokenServiceError::InvalidMetadata(_) => {
+                true
+            }
+            EvmTokenServiceError::Shared(e) => e.is_deterministic(),
+            _ => false,
+        }
+    }
+
+    /// One waiter's copy of a shared failure: plain variants are cloned, the rest are wrapped in
+    /// [`EvmTokenServiceError::Shared`].
+    pub(crate) fn from_shared(error: &Arc<EvmTokenServiceError>) -> Self {
+        match error.as_ref() {
+            EvmTokenServiceError::Multicall(message) => {
+                EvmTokenServiceError::Multicall(message.clone())
+            }
+            EvmTokenServiceError::InvalidMetadata(message) => {
+                EvmTokenServiceError::InvalidMetadata(message.clone())
+            }
+            EvmTokenServiceError::NotAContract(address) => {
+                EvmTokenServiceError::NotAContract(*address)
+            }
+            EvmTokenServiceError::ChainIdMismatch(rpc_chain_id, expected_chain_id) => {
+                EvmTokenServiceError::ChainIdMismatch(*rpc_chain_id, *expected_chain_id)
+            }
+            EvmTokenServiceError::Shared(inner) => EvmTokenServiceError::Shared(inner.clone()),
+            _ => EvmTokenServiceError::Shared(error.clone()),
+        }
+    }
 }
 
 impl From<RepoError> for EvmTokenServiceError {
@@ -41,9 +86,3 @@ impl From<tap_caip::error::Error> for EvmTokenServiceError {
         EvmTokenServiceError::CaipIdBuildFailed(error)
     }
 }
-
-impl From<BlockingError> for EvmTokenServiceError {
-    fn from(error: BlockingError) -> Self {
-        EvmTokenServiceError::BlockingError(error)
-    }
-}
@@ -5,11 +5,10 @@
 //! - Live RPC tests are `#[ignore]`; run: `cargo test -p token-api services::evm::igra -- --ignored`
 //! - Offline: wiremock tests below
 
+use super::test_support::*;
 use super::*;
 use alloy::{
-    primitives::hex,
     providers::{MULTICALL3_ADDRESS, ProviderBuilder},
-    sol_types::SolValue,
     transports::http::Http,
 };
 use serde_json::json;
@@ -34,58 +33,6 @@ fn igra_rpc_client() -> RpcClient {
     RpcClient::new(http, false)
 }
 
-fn body_is_single_eth_chain_id(req: &Request) -> bool {
-    let b = String::from_utf8_lossy(&req.body);
-    b.contains("\"eth_chainId\"") && !b.trim_start().starts_with('[')
-}
-
-fn body_is_single_eth_get_code(req: &Request) -> bool {
-    let b = String::from_utf8_lossy(&req.body);
-    b.contains("\"eth_getCode\"") && !b.trim_start().starts_with('[')
-}
-
-fn body_is_single_eth_get_account(req: &Request) -> bool {
-    let b = String::from_utf8_lossy(&req.body);
-    b.contains("\"eth_getAccount\"") && !b.trim_start().starts_with('[')
-}
-
-/// `eth_call` to the canonical Multicall3 address (aggregate), not ERC20 `eth_call`s.
-fn body_is_multicall3_aggregate_eth_call(req: &Request) -> bool {
-    let b = String::from_utf8_lossy(&req.body).to_lowercase();
-    b.contains("\"eth_call\"")
-        && !b.trim_start().starts_with('[')
-        && b.contains("ca11bde05977b3631167028862be2a173976ca11")
-}
-
-fn body_is_eth_call_with_input_prefix(prefix: &str) -> impl Fn(&Request) -> bool + '_ {
-    let p = prefix.to_lowercase();
-    move |req: &Request| {
-        let b = String::from_utf8_lossy(&req.body).to_lowercase();
-        b.contains("\"eth_call\"") && !b.trim_start().starts_with('[') && b.contains(p.as_str())
-    }
-}
-
-fn jsonrpc_eth_result_template(req: &Request, result: String) -> ResponseTemplate {
-    let id = serde_json::from_slice::<serde_json::Value>(&req.body)
-        .ok()
-        .and_then(|v| v.get("id").cloned())
-        .unwrap_or(json!(0));
-    ResponseTemplate::new(200).set_body_json(json!({
-        "jsonrpc": "2.0",
-        "id": id,
-        "result": result,
-    }))
-}
-
-fn eth_call_hex(result_word: impl SolValue) -> String {
-    let bytes = result_word.abi_encode();
-    format!("0x{}", hex::encode(bytes))
-}
-
-/// Solidity `uint8` / `decimals()` return: value 18 ABI-encoded as a 32-byte word.
-const ENCODED_DECIMALS_18: &str =
-    "0x0000000000000000000000000000000000000000000000000000000000000012";
-
 #[tokio::test]
 async fn optimistic_multicall_then_eth_get_account_empty_codehash_uses_parallel_calls() {
     let mock = MockServer::start().await;
@@ -159,8 +106,8 @@ async fn optimistic_multicall_then_eth_get_account_empty_codehash_uses_parallel_
         .await
         .expect("fetch_token after empty Multicall3 codeHash (parallel eth_call)");
 
-    assert_eq!(token.name, "Mock Igra Name");
-    assert_eq!(token.symbol, "MIGRA");
+    assert_eq!(token.name.as_deref(), Some("Mock Igra Name"));
+    assert_eq!(token.symbol.as_deref(), Some("MIGRA"));
     assert_eq!(token.decimals, 18);
 }
 
@@ -243,12 +190,13 @@ async fn optimistic_multicall_then_get_account_error_falls_back_to_get_code_and_
         .await
         .expect("fetch_token Igra-style getAccount deny + getCode");
 
-    assert_eq!(token.name, "Fallback Path");
-    assert_eq!(token.symbol, "FB");
+    assert_eq!(token.name.as_deref(), Some("Fallback Path"));
+    assert_eq!(token.symbol.as_deref(), Some("FB"));
     assert_eq!(token.decimals, 18);
 }
 
 #[tokio::test]
+#[ignore = "live Igra RPC"]
 async fn igra_mainnet_chain_id_and_multicall3_slot_effective_empty() {
     let rpc = igra_rpc_client();
     let provider = ProviderBuilder::new().connect_client(rpc);
@@ -267,13 +215,14 @@ async fn igra_mainnet_chain_id_and_multicall3_slot_effective_empty() {
 }
 
 #[tokio::test]
+#[ignore = "live Igra RPC"]
 async fn igra_fetch_token_after_failed_multicall_uses_json_batch() {
     let rpc = igra_rpc_client();
     let address: Address = IGRA_WIKAS.parse().expect("WiKAS address");
     let token = EvmTokenService::fetch_token(IGRA_CHAIN_ID, address, rpc)
         .await
         .expect("fetch_token on Igra after optimistic multicall failure");
-    assert_eq!(token.symbol, "WiKAS");
-    assert_eq!(token.name, "Wrapped Igra Kaspa");
+    assert_eq!(token.symbol.as_deref(), Some("WiKAS"));
+    assert_eq!(token.name.as_deref(), Some("Wrapped Igra Kaspa"));
     assert_eq!(token.decimals, 18);
 }
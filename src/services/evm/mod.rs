@@ -1,8 +1,19 @@
 mod erc20;
 pub mod error;
+mod refresh;
 
 #[cfg(test)]
 mod igra_tests;
+#[cfg(test)]
+mod test_support;
+#[cfg(test)]
+mod tests;
+
+use std::{
+    collections::{HashMap, HashSet},
+    sync::{Arc, Mutex},
+    time::Duration,
+};
 
 use crate::{
     repositories::Repository,
@@ -11,87 +22,576 @@ use crate::{
         error::EvmTokenServiceError,
     },
 };
-use actix_web::web;
 use alloy::{
-    primitives::{Address, B256, b256, keccak256},
-    providers::{MULTICALL3_ADDRESS, Provider, ProviderBuilder},
-    rpc::client::RpcClient,
+    primitives::{Address, B256, Bytes, KECCAK256_EMPTY, b256, keccak256},
+    providers::{
+        MULTICALL3_ADDRESS, Provider, ProviderBuilder,
+        bindings::IMulticall3::{self, Call3, aggregate3Call},
+    },
+    rpc::{client::RpcClient, json_rpc::ErrorPayload, types::TransactionRequest},
+    sol_types::SolCall,
+    transports::TransportError,
 };
-use tap_caip::{AccountId, ChainId as CaipChainId};
+use chrono::{TimeDelta, Utc};
+use futures::{StreamExt, stream};
+use log::{debug, error};
+use tap_caip::ChainId as CaipChainId;
+use tokio::sync::watch;
+
+pub use refresh::RefreshPolicy;
 
 use crate::{
-    repositories::sqlite::evm_token::SqliteEvmTokenRepository,
-    token::{Token, TokenId},
+    repositories::{DynEvmTokenRepository, EvmTokenRepository, RepoError},
+    token::{ERC20_NAMESPACE, MetadataAbi, NegativeReason, NegativeTokenResult, Token, TokenId},
     types::ChainId,
 };
 
 #[derive(Clone)]
 pub struct EvmTokenService {
-    repository: SqliteEvmTokenRepository,
+    repository: DynEvmTokenRepository,
+    /// How long a deterministic failure (no code, not ERC-20) is served from storage instead of
+    /// asking the chain again; zero disables the negative cache.
+    negative_ttl: Duration,
+    /// Single-flight lookups by token: concurrent callers for the same uncached token share one
+    /// fetch instead of each asking the chain.
+    in_flight: Arc<Mutex<HashMap<TokenId, watch::Receiver<Option<SharedResult>>>>>,
+}
+
+/// Outcome of a shared lookup, published once by the caller that runs it.
+type SharedResult = Result<Token, Arc<EvmTokenServiceError>>;
+
+/// A caller's part in a token's single-flight lookup.
+enum Claim {
+    /// Runs the lookup and publishes its outcome through [`EvmTokenService::publish`].
+    Leader(watch::Sender<Option<SharedResult>>),
+    /// Waits for the leader's outcome.
+    Follower(watch::Receiver<Option<SharedResult>>),
 }
 
 const EVM_NAMESPACE: &str = "eip155";
 
+/// Tokens per Multicall3 `aggregate3` in the batch path (three calls each); keeps calldata and
+/// return data well under typical node `eth_call` limits.
+const MULTICALL_MAX_TOKENS_PER_CALL: usize = 300;
+
+/// Tokens fetched at once with per-token `eth_call`s when the chain has no canonical Multicall3.
+const RPC_BATCH_CONCURRENCY: usize = 16;
+
+/// Per-address outcome of a batch lookup; the outer error in batch APIs applies to the whole chain.
+pub type TokenResult = Result<Token, EvmTokenServiceError>;
+
 /// Keccak-256 of the canonical Multicall3 **deployed bytecode** (matches `codeHash` from `eth_getAccount`).
 const MULTICALL3_DEPLOYED_CODE_HASH: B256 =
     b256!("0xd5c15df687b16f2ff992fc8d767b4216323184a2bbc6ee2f9c398c318e770891");
 
-/// True if Multicall3 is deployed at [`MULTICALL3_ADDRESS`]: prefer `eth_getAccount.codeHash`, fall
-/// back to `keccak256(eth_getCode)` when the node disallows or omits `eth_getAccount`.
-async fn multicall3_matches_canonical_deployment<P: Provider>(
+/// Code hash of `address`: prefer `eth_getAccount.codeHash`, fall back to `keccak256(eth_getCode)`
+/// when the node disallows or omits `eth_getAccount`.
+async fn code_hash_at<P: Provider>(
     provider: &P,
-) -> Result<bool, EvmTokenServiceError> {
-    match provider.get_account(MULTICALL3_ADDRESS).await {
-        Ok(acc) => Ok(acc.code_hash == MULTICALL3_DEPLOYED_CODE_HASH),
+    address: Address,
+) -> Result<B256, EvmTokenServiceError> {
+    match provider.get_account(address).await {
+        Ok(acc) => Ok(acc.code_hash),
         Err(_) => {
             let code = provider
-                .get_code_at(MULTICALL3_ADDRESS)
+                .get_code_at(address)
                 .await
                 .map_err(EvmTokenServiceError::Chain)?;
-            Ok(!code.is_empty() && keccak256(&code) == MULTICALL3_DEPLOYED_CODE_HASH)
+            Ok(keccak256(&code))
         }
     }
 }
 
+/// True if Multicall3 is deployed at [`MULTICALL3_ADDRESS`] (see [`code_hash_at`]).
+async fn multicall3_matches_canonical_deployment<P: Provider>(
+    provider: &P,
+) -> Result<bool, EvmTokenServiceError> {
+    Ok(code_hash_at(provider, MULTICALL3_ADDRESS).await? == MULTICALL3_DEPLOYED_CODE_HASH)
+}
+
+/// Turns a metadata decode failure into [`EvmTokenServiceError::NotAContract`] when `address` has no
+/// code (EOA or undeployed); other outcomes pass through unchanged.
+async fn classify_metadata_failure<P: Provider>(
+    provider: &P,
+    address: Address,
+    result: Result<TokenMetadata, EvmTokenServiceError>,
+) -> Result<TokenMetadata, EvmTokenServiceError> {
+    if !matches!(result, Err(EvmTokenServiceError::InvalidMetadata(_))) {
+        return result;
+    }
+    let code_hash = code_hash_at(provider, address).await?;
+    if code_hash == KECCAK256_EMPTY || code_hash == B256::ZERO {
+        return Err(EvmTokenServiceError::NotAContract(address));
+    }
+    result
+}
+
+fn token_id(chain_id: ChainId, address: Address) -> Result<TokenId, EvmTokenServiceError> {
+    let chain_id = CaipChainId::new(EVM_NAMESPACE, &chain_id.to_string())?;
+    Ok(TokenId::new(
+        chain_id,
+        ERC20_NAMESPACE,
+        &address.to_string(),
+    )?)
+}
+
+async fn ensure_chain_id<P: Provider>(
+    provider: &P,
+    chain_id: ChainId,
+) -> Result<(), EvmTokenServiceError> {
+    let chain_id_from_provider: u64 = provider.get_chain_id().await?;
+
+    if chain_id_from_provider != chain_id as u64 {
+        return Err(EvmTokenServiceError::ChainIdMismatch(
+            chain_id_from_provider,
+            chain_id as u64,
+        ));
+    }
+    Ok(())
+}
+
+/// Current block number, recorded as the source of freshly read metadata. Best effort: metadata is
+/// still served when the RPC does not answer `eth_blockNumber`.
+async fn head_block<P: Provider>(provider: &P) -> Option<u64> {
+    provider
+        .get_block_number()
+        .await
+        .inspect_err(|e| debug!("eth_blockNumber failed: {e}"))
+        .ok()
+}
+
+/// ERC-20 metadata as read from chain, before it is tied to a [`TokenId`].
+struct TokenMetadata {
+    name: Option<String>,
+    symbol: Option<String>,
+    decimals: u8,
+    metadata_abi: MetadataAbi,
+}
+
+impl TokenMetadata {
+    /// `name`/`symbol` are optional in ERC-20: a revert (`None`) or undecodable return leaves the
+    /// field empty. `decimals` is required.
+    fn from_returns(
+        name: Option<&[u8]>,
+        symbol: Option<&[u8]>,
+        decimals: &[u8],
+    ) -> Result<Self, EvmTokenServiceError> {
+        let decimals = require_decoded(decimalsCall::abi_decode_returns(decimals), "decimals")?;
+        let name = name.and_then(|data| decode_string_or_bytes32(data, "name").ok());
+        let symbol = symbol.and_then(|data| decode_string_or_bytes32(data, "symbol").ok());
+
+        let metadata_abi = if [&name, &symbol]
+            .iter()
+            .any(|field| matches!(field, Some((_, MetadataAbi::Bytes32))))
+        {
+            MetadataAbi::Bytes32
+        } else {
+            MetadataAbi::String
+        };
+
+        Ok(Self {
+            name: name.map(|(value, _)| value),
+            symbol: symbol.map(|(value, _)| value),
+            decimals,
+            metadata_abi,
+        })
+    }
+
+    fn into_token(self, id: TokenId, source_block: Option<u64>) -> Token {
+        Token::new(id, self.name, self.symbol, self.decimals, self.metadata_abi)
+            .with_source_block(source_block)
+    }
+}
+
+/// Return data of an optional `aggregate3` call, `None` if it reverted.
+fn call3_optional_return_data(result: &IMulticall3::Result) -> Option<&[u8]> {
+    result.success.then_some(&result.returnData[..])
+}
+
+/// `None` if the `eth_call` reverted. Any other failure, including error responses such as rate
+/// limits or a node missing the block, is propagated so the lookup can be retried.
+fn optional_call_result(
+    result: Result<Bytes, TransportError>,
+) -> Result<Option<Bytes>, EvmTokenServiceError> {
+    match result {
+        Ok(data) => Ok(Some(data)),
+        Err(e) if e.as_error_resp().is_some_and(is_revert) => Ok(None),
+        Err(e) => Err(e.into()),
+    }
+}
+
+/// Whether an `eth_call` error response is the contract reverting: code 3 (revert with data), or
+/// a message like geth's "execution reverted" that is not also a rate limit.
+fn is_revert(error: &ErrorPayload) -> bool {
+    error.code == 3
+        || (!error.is_retry_err() && error.message.to_ascii_lowercase().contains("revert"))
+}
+
+/// Return data of one `aggregate3` call; a revert (`success == false`) is reported like a decode
+/// failure.
+fn call3_return_data<'a>(
+    result: &'a IMulticall3::Result,
+    field: &'static str,
+) -> Result<&'a [u8], EvmTokenServiceError> {
+    if !result.success {
+        return Err(EvmTokenServiceError::InvalidMetadata(format!(
+            "{field} call reverted"
+        )));
+    }
+    Ok(&result.returnData)
+}
+
+/// Decodes a `name()`/`symbol()` return as `string`, falling back to a null-padded `bytes32`
+/// (MKR, SAI) interpreted as UTF-8.
+fn decode_string_or_bytes32(
+    data: &[u8],
+    field: &'static str,
+) -> Result<(String, MetadataAbi), EvmTokenServiceError> {
+    let string_err = match nameCall::abi_decode_returns(data) {
+        Ok(value) => return Ok((value, MetadataAbi::String)),
+        Err(e) => e,
+    };
+
+    if data.len() != 32 {
+        return require_decoded(Err(string_err), field);
+    }
+
+    let end = data.iter().rposition(|b| *b != 0).map_or(0, |i| i + 1);
+    let value = require_decoded(std::str::from_utf8(&data[..end]), field)?;
+    Ok((value.to_owned(), MetadataAbi::Bytes32))
+}
+
 fn require_decoded<T, E: std::fmt::Display>(
     result: Result<T, E>,
     field: &'static str,
 ) -> Result<T, EvmTokenServiceError> {
     result.map_err(|e| {
-        EvmTokenServiceError::Multicall(format!("Failed to fetch and decode {field} call: {e}"))
+        EvmTokenServiceError::InvalidMetadata(format!(
+            "Failed to fetch and decode {field} call: {e}"
+        ))
+    })
+}
+
+/// Stored outcome for `id`: a token, a still-fresh negative result, or `None` when the chain has to
+/// be asked.
+async fn stored_result(
+    repo: &dyn EvmTokenRepository,
+    id: TokenId,
+    address: Address,
+    negative_ttl: Duration,
+) -> Result<Option<TokenResult>, RepoError> {
+    if let Some(token) = Repository::<Token>::get(repo, id.clone()).await? {
+        return Ok(Some(Ok(token)));
+    }
+
+    if negative_ttl.is_zero() {
+        return Ok(None);
+    }
+
+    let Some(negative) = Repository::<NegativeTokenResult>::get(repo, id).await? else {
+        return Ok(None);
+    };
+
+    let ttl = TimeDelta::from_std(negative_ttl).unwrap_or(TimeDelta::MAX);
+    if Utc::now() - negative.recorded_at >= ttl {
+        return Ok(None);
+    }
+
+    Ok(Some(Err(negative_error(address, &negative))))
+}
+
+/// Rebuilds the error a negative result was recorded for.
+fn negative_error(address: Address, negative: &NegativeTokenResult) -> EvmTokenServiceError {
+    match negative.reason {
+        NegativeReason::NotAContract => EvmTokenServiceError::NotAContract(address),
+        NegativeReason::InvalidMetadata => {
+            EvmTokenServiceError::InvalidMetadata(negative.message.clone())
+        }
+    }
+}
+
+/// Negative result for a fetch failure, if the failure is deterministic. Transport errors, chain id
+/// mismatches and the like are never remembered.
+fn negative_result(id: TokenId, error: &EvmTokenServiceError) -> Option<NegativeTokenResult> {
+    let reason = match error {
+        EvmTokenServiceError::NotAContract(_) => NegativeReason::NotAContract,
+        EvmTokenServiceError::InvalidMetadata(_) => NegativeReason::InvalidMetadata,
+        _ => return None,
+    };
+    Some(NegativeTokenResult {
+        id,
+        reason,
+        message: error.to_string(),
+        recorded_at: Utc::now(),
     })
 }
 
 impl EvmTokenService {
-    pub fn new(repository: SqliteEvmTokenRepository) -> Self {
-        Self { repository }
+    pub fn new(repository: DynEvmTokenRepository, negative_ttl: Duration) -> Self {
+        Self {
+            repository,
+            negative_ttl,
+            in_flight: Arc::new(Mutex::new(HashMap::new())),
+        }
+    }
+
+    /// Stores `result`: the token on success, a negative result on a deterministic failure. Failing
+    /// to store a negative result is logged and does not replace the original error.
+    async fn record(&self, id: TokenId, result: TokenResult) -> TokenResult {
+        match result {
+            Ok(token) => {
+                Repository::<Token>::save(self.repository.as_ref(), &token).await?;
+                Ok(token)
+            }
+            Err(e) => {
+                if !self.negative_ttl.is_zero()
+                    && let Some(negative) = negative_result(id, &e)
+                {
+                    // An expired entry is refreshed in place, restarting its TTL.
+                    let repo = self.repository.as_ref();
+                    let saved = match repo.update(&negative).await {
+                        Err(RepoError::NotFound) => repo.save(&negative).await,
+                        saved => saved,
+                    };
+                    if let Err(save_error) = saved {
+                        error!("Failed to save negative result: {save_error}");
+                    }
+                }
+                Err(e)
+            }
+        }
+    }
+
+    /// Leads the lookup of `token_id` unless another caller already is.
+    fn claim(&self, token_id: &TokenId) -> Claim {
+        let mut in_flight = self
+            .in_flight
+            .lock()
+            .expect("in-flight lookups lock poisoned");
+        // A closed channel means its leader was dropped mid-way; the next caller takes over.
+        if let Some(receiver) = in_flight.get(token_id)
+            && receiver.has_changed().is_ok()
+        {
+            return Claim::Follower(receiver.clone());
+        }
+        let (sender, receiver) = watch::channel(None);
+        in_flight.insert(token_id.clone(), receiver);
+        Claim::Leader(sender)
     }
 
+    /// Hands a leader's outcome to its followers and ends the lookup.
+    fn publish(
+        &self,
+        token_id: &TokenId,
+        leader: watch::Sender<Option<SharedResult>>,
+        result: SharedResult,
+    ) {
+        leader.send_replace(Some(result));
+        let mut in_flight = self
+            .in_flight
+            .lock()
+            .expect("in-flight lookups lock poisoned");
+        if in_flight
+            .get(token_id)
+            .is_some_and(|current| current.same_channel(&leader.subscribe()))
+        {
+            in_flight.remove(token_id);
+        }
+    }
+
+    /// The leader's outcome; `None` if the leader was dropped before publishing one.
+    async fn follow(mut follower: watch::Receiver<Option<SharedResult>>) -> Option<SharedResult> {
+        follower
+            .wait_for(Option::is_some)
+            .await
+            .ok()
+            .and_then(|result| result.clone())
+    }
+
+    /// Token from storage, or fetched from the chain and stored. Concurrent calls for the same
+    /// token, single or batch, share a single lookup.
     pub async fn get_or_fetch_token(
         &self,
         chain_id: ChainId,
         address: Address,
         rpc: RpcClient,
     ) -> Result<Token, EvmTokenServiceError> {
-        let token_id: TokenId = TokenId::new(
-            CaipChainId::new(EVM_NAMESPACE, &chain_id.to_string()).unwrap(),
-            &address.to_string(),
-        )?;
+        let token_id = token_id(chain_id, address)?;
+
+        loop {
+            match self.claim(&token_id) {
+                Claim::Leader(leader) => {
+                    let result = self
+                        .lookup_token(token_id.clone(), chain_id, address, rpc)
+                        .await
+                        .map_err(Arc::new);
+                    self.publish(&token_id, leader, result.clone());
+                    return result.map_err(|e| EvmTokenServiceError::from_shared(&e));
+                }
+                Claim::Follower(follower) => {
+                    if let Some(result) = Self::follow(follower).await {
+                        return result.map_err(|e| EvmTokenServiceError::from_shared(&e));
+                    }
+                }
+            }
+        }
+    }
+
+    /// Storage first, then the chain; what [`Self::get_or_fetch_token`] runs once per token.
+    async fn lookup_token(
+        &self,
+        token_id: TokenId,
+        chain_id: ChainId,
+        address: Address,
+        rpc: RpcClient,
+    ) -> Result<Token, EvmTokenServiceError> {
+        let stored = stored_result(
+            self.repository.as_ref(),
+            token_id.clone(),
+            address,
+            self.negative_ttl,
+        )
+        .await?;
+
+        if let Some(result) = stored {
+            return result;
+        }
+
+        let result = Self::fetch_token(chain_id, address, rpc).await;
+        if let Err(e) = &result
+            && !e.is_deterministic()
+        {
+            return result;
+        }
+
+        self.record(token_id, result).await
+    }
+
+    /// Batch variant of [`Self::get_or_fetch_token`] for a single chain: repository hits are served
+    /// directly and all misses share Multicall3 round trips. Tokens another single or batch lookup
+    /// is already resolving are awaited instead of fetched again. Returns one entry per distinct
+    /// address, in first-seen order. The outer error (chain id mismatch, transport failure) applies
+    /// to every address; per-token failures are reported in the entries.
+    pub async fn get_or_fetch_tokens(
+        &self,
+        chain_id: ChainId,
+        addresses: &[Address],
+        rpc: RpcClient,
+    ) -> Result<Vec<(Address, TokenResult)>, EvmTokenServiceError> {
+        let mut seen = HashSet::new();
+        let unique: Vec<Address> = addresses
+            .iter()
+            .copied()
+            .filter(|a| seen.insert(*a))
+            .collect();
 
-        // Run potentially blocking repository access on a blocking thread pool.
-        // Clone the repository so we don't capture &self into the closure.
-        let repo = self.repository.clone();
-        let token = web::block(move || repo.get(token_id)).await??;
+        let ids = unique
+            .iter()
+            .map(|a| token_id(chain_id, *a))
+            .collect::<Result<Vec<_>, _>>()?;
 
-        if let Some(token) = token {
-            return Ok(token);
+        let mut leaders = Vec::new();
+        let mut followers = Vec::new();
+        for (id, &address) in ids.into_iter().zip(&unique) {
+            match self.claim(&id) {
+                Claim::Leader(leader) => leaders.push((id, address, leader)),
+                Claim::Follower(follower) => followers.push((address, follower)),
+            }
+        }
+
+        // Leaders are resolved and published before any follower is awaited, so batches that
+        // follow each other's tokens cannot wait on each other.
+        let mut results: HashMap<Address, TokenResult> = HashMap::new();
+        for (address, result) in self.lookup_tokens(chain_id, leaders, rpc.clone()).await? {
+            results.insert(
+                address,
+                result.map_err(|e| EvmTokenServiceError::from_shared(&e)),
+            );
+        }
+        for (address, follower) in followers {
+            let result = match Self::follow(follower).await {
+                Some(result) => result.map_err(|e| EvmTokenServiceError::from_shared(&e)),
+                None => {
+                    self.get_or_fetch_token(chain_id, address, rpc.clone())
+                        .await
+                }
+            };
+            results.insert(address, result);
         }
 
-        let token = Self::fetch_token(chain_id, address, rpc).await?;
+        Ok(unique
+            .into_iter()
+            .map(|address| {
+                let result = results.remove(&address).unwrap_or_else(|| {
+                    Err(EvmTokenServiceError::Multicall(format!(
+                        "No result for {address}"
+                    )))
+                });
+                (address, result)
+            })
+            .collect())
+    }
 
-        self.repository.save(&token)?;
+    /// Storage first, then one batched fetch for the misses; what [`Self::get_or_fetch_tokens`]
+    /// runs for the tokens it leads. Every outcome is published to followers. On an outer error
+    /// the leaders are dropped unpublished, so their followers look the tokens up themselves.
+    async fn lookup_tokens(
+        &self,
+        chain_id: ChainId,
+        leaders: Vec<(TokenId, Address, watch::Sender<Option<SharedResult>>)>,
+        rpc: RpcClient,
+    ) -> Result<Vec<(Address, SharedResult)>, EvmTokenServiceError> {
+        let mut stored = Vec::with_capacity(leaders.len());
+        for (id, address, _) in &leaders {
+            stored.push(
+                stored_result(
+                    self.repository.as_ref(),
+                    id.clone(),
+                    *address,
+                    self.negative_ttl,
+                )
+                .await?,
+            );
+        }
 
-        Ok(token)
+        let misses: Vec<Address> = leaders
+            .iter()
+            .zip(&stored)
+            .filter(|(_, stored)| stored.is_none())
+            .map(|((_, address, _), _)| *address)
+            .collect();
+
+        let mut fetched: HashMap<Address, TokenResult> = HashMap::new();
+        if !misses.is_empty() {
+            for (address, result) in Self::fetch_tokens(chain_id, &misses, rpc).await? {
+                let result = match &result {
+                    Err(e) if !e.is_deterministic() => result,
+                    _ => match token_id(chain_id, address) {
+                        Ok(id) => self.record(id, result).await,
+                        Err(e) => Err(e),
+                    },
+                };
+                fetched.insert(address, result);
+            }
+        }
+
+        Ok(leaders
+            .into_iter()
+            .zip(stored)
+            .map(|((id, address, leader), stored)| {
+                let result = match stored {
+                    Some(result) => result,
+                    None => fetched.remove(&address).unwrap_or_else(|| {
+                        Err(EvmTokenServiceError::Multicall(format!(
+                            "No result for {address}"
+                        )))
+                    }),
+                }
+                .map_err(Arc::new);
+                self.publish(&id, leader, result.clone());
+                (address, result)
+            })
+            .collect())
     }
 
     async fn fetch_token(
@@ -101,72 +601,155 @@ impl EvmTokenService {
     ) -> Result<Token, EvmTokenServiceError> {
         let provider = ProviderBuilder::new().connect_client(rpc.clone());
 
-        let chain_id_from_provider: u64 = provider.get_chain_id().await?;
+        let (chain, source_block) =
+            tokio::join!(ensure_chain_id(&provider, chain_id), head_block(&provider));
+        chain?;
 
-        if chain_id_from_provider != chain_id as u64 {
-            return Err(EvmTokenServiceError::ChainIdMismatch(
-                chain_id_from_provider,
-                chain_id as u64,
-            ));
-        }
-
-        let token_contract = ERC20::new(address, &provider);
-        let multicall = provider
-            .multicall()
-            .add(token_contract.name())
-            .add(token_contract.symbol())
-            .add(token_contract.decimals());
-
-        let (name, symbol, decimals) = match multicall.aggregate().await {
-            Ok(fields) => fields,
+        let metadata = match Self::aggregate_token_metadata(&[address], &provider).await {
+            Ok(mut metadata) => metadata
+                .pop()
+                .expect("aggregate3 yields one result per address"),
             Err(multicall_err) => {
                 if multicall3_matches_canonical_deployment(&provider).await? {
-                    return Err(EvmTokenServiceError::Multicall(multicall_err.to_string()));
+                    return Err(multicall_err);
                 }
-                Self::fetch_token_metadata_with_rpc_batch(address, &provider).await?
+                Self::fetch_token_metadata_with_rpc_batch(address, &provider).await
             }
         };
+        let metadata = classify_metadata_failure(&provider, address, metadata).await?;
+
+        Ok(metadata.into_token(token_id(chain_id, address)?, source_block))
+    }
 
-        let chain_id = CaipChainId::new(EVM_NAMESPACE, &chain_id.to_string())
-            .expect("Failed to create CAIP chain id")
-            .clone();
+    /// Fetches metadata for many tokens on one chain with one `aggregate3` per
+    /// [`MULTICALL_MAX_TOKENS_PER_CALL`] tokens. Calls allow failure, so a bad address only fails its
+    /// own entry. Without a canonical Multicall3 falls back to per-token `eth_call`s, up to
+    /// [`RPC_BATCH_CONCURRENCY`] tokens at a time.
+    async fn fetch_tokens(
+        chain_id: ChainId,
+        addresses: &[Address],
+        rpc: RpcClient,
+    ) -> Result<Vec<(Address, TokenResult)>, EvmTokenServiceError> {
+        let provider = ProviderBuilder::new().connect_client(rpc);
 
-        let account_id = AccountId::new(chain_id.clone(), &address.to_string())
-            .expect("Failed to create account id");
+        let (chain, source_block) =
+            tokio::join!(ensure_chain_id(&provider, chain_id), head_block(&provider));
+        chain?;
 
-        let token: Token = Token {
-            id: account_id,
-            name,
-            symbol,
-            decimals,
-        };
+        let mut results = Vec::with_capacity(addresses.len());
+        for chunk in addresses.chunks(MULTICALL_MAX_TOKENS_PER_CALL) {
+            let metadata = match Self::aggregate_token_metadata(chunk, &provider).await {
+                Ok(metadata) => metadata,
+                Err(multicall_err) => {
+                    if multicall3_matches_canonical_deployment(&provider).await? {
+                        return Err(multicall_err);
+                    }
+                    stream::iter(chunk.iter().copied())
+                        .map(|address| {
+                            Self::fetch_token_metadata_with_rpc_batch(address, &provider)
+                        })
+                        .buffered(RPC_BATCH_CONCURRENCY)
+                        .collect()
+                        .await
+                }
+            };
 
-        Ok(token)
+            for (&address, metadata) in chunk.iter().zip(metadata) {
+                let token = classify_metadata_failure(&provider, address, metadata)
+                    .await
+                    .and_then(|metadata| {
+                        Ok(metadata.into_token(token_id(chain_id, address)?, source_block))
+                    });
+                results.push((address, token));
+            }
+        }
+
+        Ok(results)
     }
 
-    /// Reads ERC-20 metadata via three parallel `eth_call`s (no Multicall3).
+    /// `name`/`symbol`/`decimals` for every address in a single Multicall3 `aggregate3`. Calls allow
+    /// failure: a reverted `name`/`symbol` leaves that field empty, a reverted `decimals` fails only
+    /// its own entry; the outer error means the
+    /// aggregate itself failed (e.g. no Multicall3 at the canonical address).
+    async fn aggregate_token_metadata<P: Provider>(
+        addresses: &[Address],
+        provider: &P,
+    ) -> Result<Vec<Result<TokenMetadata, EvmTokenServiceError>>, EvmTokenServiceError> {
+        let calls: Vec<Call3> = addresses
+            .iter()
+            .flat_map(|&target| {
+                [
+                    nameCall {}.abi_encode(),
+                    symbolCall {}.abi_encode(),
+                    decimalsCall {}.abi_encode(),
+                ]
+                .map(|call_data| Call3 {
+                    target,
+                    allowFailure: true,
+                    callData: call_data.into(),
+                })
+            })
+            .collect();
+
+        let request = TransactionRequest::default()
+            .to(MULTICALL3_ADDRESS)
+            .input(aggregate3Call { calls }.abi_encode().into());
+
+        let returned = provider
+            .call(request)
+            .decode_resp::<aggregate3Call>()
+            .await?
+            .map_err(|e| EvmTokenServiceError::Multicall(e.to_string()))?;
+
+        if returned.len() != addresses.len() * 3 {
+            return Err(EvmTokenServiceError::Multicall(format!(
+                "aggregate3 returned {} results for {} calls",
+                returned.len(),
+                addresses.len() * 3
+            )));
+        }
+
+        Ok(returned
+            .chunks_exact(3)
+            .map(|fields| {
+                TokenMetadata::from_returns(
+                    call3_optional_return_data(&fields[0]),
+                    call3_optional_return_data(&fields[1]),
+                    call3_return_data(&fields[2], "decimals")?,
+                )
+            })
+            .collect())
+    }
+
+    /// Reads ERC-20 metadata via three parallel `eth_call`s (no Multicall3). Only `decimals` must
+    /// succeed.
     async fn fetch_token_metadata_with_rpc_batch<P: Provider>(
         token_address: Address,
         provider: &P,
-    ) -> Result<(String, String, u8), EvmTokenServiceError> {
+    ) -> Result<TokenMetadata, EvmTokenServiceError> {
         let token_contract = ERC20::new(token_address, provider);
 
         let name_call = token_contract.name().into_transaction_request();
         let symbol_call = token_contract.symbol().into_transaction_request();
         let decimals_call = token_contract.decimals().into_transaction_request();
 
-        let (name_result, symbol_result, decimals_result) = tokio::try_join!(
-            provider.call(name_call).decode_resp::<nameCall>(),
-            provider.call(symbol_call).decode_resp::<symbolCall>(),
-            provider.call(decimals_call).decode_resp::<decimalsCall>(),
-        )?;
-
-        let (name, symbol, decimals) = (
-            require_decoded(name_result, "name")?,
-            require_decoded(symbol_result, "symbol")?,
-            require_decoded(decimals_result, "decimals")?,
+        let (name, symbol, decimals) = tokio::join!(
+            provider.call(name_call),
+            provider.call(symbol_call),
+            provider.call(decimals_call),
         );
 
-        Ok((name, symbol, decimals))
+        let name = optional_call_result(name)?;
+        let symbol = optional_call_result(symbol)?;
+        // `decimals` is required: a revert fails the token like it does in `aggregate3`.
+        let decimals = optional_call_result(decimals)?.ok_or_else(|| {
+            EvmTokenServiceError::InvalidMetadata("decimals call reverted".to_string())
+        })?;
+
+        TokenMetadata::from_returns(
+            name.as_ref().map(|data| &data[..]),
+            symbol.as_ref().map(|data| &data[..]),
+            &decimals,
+        )
     }
 }
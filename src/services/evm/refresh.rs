@@ -0,0 +1,511 @@
+//! Background re-verification of stored tokens. Metadata can change after a proxy upgrade, so
+//! tokens last verified longer than [`RefreshPolicy::max_age`] ago are fetched again and updated in
+//! place. Lookups keep serving the stored copy while this runs.
+//!
+//! Every replica runs the refresh against the shared storage. Each run claims its batch through
+//! [`EvmTokenRepository::claim_stale`](crate::repositories::EvmTokenRepository::claim_stale), so
+//! replicas refresh different tokens, and a token that fails is left alone for
+//! [`RefreshPolicy::retry_after`] instead of being picked first by every run.
+
+use std::{collections::BTreeMap, time::Duration};
+
+use alloy::primitives::Address;
+use chrono::{DateTime, TimeDelta, Utc};
+use log::{info, warn};
+use tokio::task::JoinHandle;
+
+use super::{EvmTokenService, error::EvmTokenServiceError};
+use crate::{
+    repositories::Repository, services::provider::ProviderService, token::Token, types::ChainId,
+};
+
+#[derive(Debug, Clone)]
+pub struct RefreshPolicy {
+    /// Time between refresh runs.
+    pub interval: Duration,
+    /// Tokens last verified longer ago than this are re-fetched.
+    pub max_age: Duration,
+    /// Most tokens re-fetched per run, least recently verified first.
+    pub batch_size: usize,
+    /// How long a token whose refresh failed waits before it is tried again.
+    pub retry_after: Duration,
+}
+
+/// Outcome of one refresh run.
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct RefreshReport {
+    /// Tokens confirmed against the chain, changed or not.
+    pub verified: usize,
+    /// Verified tokens whose metadata differed from the stored copy.
+    pub changed: usize,
+    /// Tokens that could not be re-fetched, or came back without a field they had; they keep their
+    /// stored copy and are retried after [`RefreshPolicy::retry_after`].
+    pub failed: usize,
+}
+
+impl EvmTokenService {
+    /// Runs [`Self::refresh_stale`] every [`RefreshPolicy::interval`] until the task is aborted.
+    /// Safe to run on every replica: concurrent runs claim different tokens.
+    pub fn spawn_refresh(
+        &self,
+        providers: ProviderService,
+        policy: RefreshPolicy,
+    ) -> JoinHandle<()> {
+        let service = self.clone();
+        tokio::spawn(async move {
+            let mut ticker = tokio::time::interval(policy.interval);
+            loop {
+                ticker.tick().await;
+                match service.refresh_stale(&providers, &policy).await {
+                    Ok(report) if report == RefreshReport::default() => {}
+                    Ok(report) => info!(
+                        "Token refresh: {} verified, {} changed, {} failed",
+                        report.verified, report.changed, report.failed
+                    ),
+                    Err(e) => warn!("Token refresh failed: {e}"),
+                }
+            }
+        })
+    }
+
+    /// Re-fetches one batch of stale tokens over the Chainlist RPCs of their chains. Only storage
+    /// failures fail the whole run; chain failures are counted per token.
+    pub async fn refresh_stale(
+        &self,
+        providers: &ProviderService,
+        policy: &RefreshPolicy,
+    ) -> Result<RefreshReport, EvmTokenServiceError> {
+        let now = Utc::now();
+        let stale = self
+            .repository
+            .claim_stale(
+                before(now, policy.max_age),
+                before(now, policy.retry_after),
+                now,
+                policy.batch_size,
+            )
+            .await?;
+
+        let mut by_chain: BTreeMap<ChainId, Vec<(Address, Token)>> = BTreeMap::new();
+        let mut report = RefreshReport::default();
+        for token in stale {
+            match chain_and_address(&token) {
+                Some((chain_id, address)) => {
+                    by_chain.entry(chain_id).or_default().push((address, token))
+                }
+                None => {
+                    warn!("Cannot refresh token with id {}", token.id);
+                    report.failed += 1;
+                }
+            }
+        }
+
+        for (chain_id, stored) in by_chain {
+            let rpc = match providers.rpc_client_for_chain(chain_id).await {
+                Ok(Some(rpc)) => rpc,
+                Ok(None) => {
+                    warn!("Cannot refresh tokens on chain {chain_id}: no RPC URLs");
+                    report.failed += stored.len();
+                    continue;
+                }
+                Err(e) => {
+                    warn!("Cannot refresh tokens on chain {chain_id}: {e}");
+                    report.failed += stored.len();
+                    continue;
+                }
+            };
+
+            let addresses: Vec<Address> = stored.iter().map(|(address, _)| *address).collect();
+            let fetched = match Self::fetch_tokens(chain_id, &addresses, rpc).await {
+                Ok(fetched) => fetched,
+                Err(e) => {
+                    warn!("Cannot refresh tokens on chain {chain_id}: {e}");
+                    report.failed += stored.len();
+                    continue;
+                }
+            };
+
+            let mut updates = Vec::with_capacity(fetched.len());
+            for ((_, stored), (address, result)) in stored.into_iter().zip(fetched) {
+                match result {
+                    Ok(fresh) if loses_fields(&stored, &fresh) => {
+                        warn!(
+                            "Not refreshing token {address} on chain {chain_id}: {:?} came back empty",
+                            fresh.missing_fields
+                        );
+                        report.failed += 1;
+                    }
+                    Ok(fresh) if fresh.same_metadata(&stored) => {
+                        updates.push(Token {
+                            fetched_at: stored.fetched_at,
+                            ..fresh
+                        });
+                    }
+                    Ok(fresh) => {
+                        info!(
+                            "Token {} changed: name {:?} -> {:?}, symbol {:?} -> {:?}, decimals {} -> {}",
+                            fresh.id,
+                            stored.name,
+                            fresh.name,
+                            stored.symbol,
+                            fresh.symbol,
+                            stored.decimals,
+                            fresh.decimals
+                        );
+                        report.changed += 1;
+                        updates.push(fresh);
+                    }
+                    Err(e) => {
+                        warn!("Cannot refresh token {address} on chain {chain_id}: {e}");
+                        report.failed += 1;
+                    }
+                }
+            }
+
+            report.verified += updates.len();
+            for token in &updates {
+                Repository::<Token>::update(self.repository.as_ref(), token).await?;
+            }
+        }
+
+        Ok(report)
+    }
+}
+
+/// `now - age`, or the earliest representable time if that underflows.
+fn before(now: DateTime<Utc>, age: Duration) -> DateTime<Utc> {
+    TimeDelta::from_std(age)
+        .ok()
+        .and_then(|age| now.checked_sub_signed(age))
+        .unwrap_or(DateTime::<Utc>::MIN_UTC)
+}
+
+/// Whether `fresh` lacks a field `stored` has. An undecodable return from a flaky node looks the
+/// same as a token without the field, so the stored value is kept and the token retried instead.
+fn loses_fields(stored: &Token, fresh: &Token) -> bool {
+    fresh
+        .missing_fields
+        .iter()
+        .any(|field| !stored.missing_fields.contains(field))
+}
+
+fn chain_and_address(token: &Token) -> Option<(ChainId, Address)> {
+    let chain_id = token.id.chain_id().reference().parse::<ChainId>().ok()?;
+    let address = token.id.reference().parse::<Address>().ok()?;
+    Some((chain_id, address))
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+
+    use alloy::{primitives::U256, providers::bindings::IMulticall3};
+    use serde_json::json;
+    use wiremock::{
+        Mock, MockServer, Request, ResponseTemplate,
+        matchers::{method, path},
+    };
+
+    use super::*;
+    use crate::{
+        repositories::memory::evm_token::InMemoryEvmTokenRepository,
+        services::{chainlist::ChainlistService, evm::test_support::*},
+        token::MetadataAbi,
+    };
+
+    const CHAIN_ID: ChainId = 1;
+
+    async fn providers(rpc: &MockServer) -> (MockServer, ProviderService) {
+        let list = MockServer::start().await;
+        Mock::given(method("GET"))
+            .and(path("/rpcs.json"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(json!([{
+                "name": "Test",
+                "chain": "TST",
+                "chainId": CHAIN_ID,
+                "rpc": [{ "url": rpc.uri() }],
+            }])))
+            .mount(&list)
+            .await;
+        let chainlist = ChainlistService::with_client_and_url(
+            Duration::from_secs(3600),
+            reqwest::Client::new(),
+            format!("{}/rpcs.json", list.uri()),
+        );
+        (
+            list,
+            ProviderService::new(chainlist, Duration::from_secs(3600)),
+        )
+    }
+
+    fn stored_token(address: Address, symbol: &str, verified_at: DateTime<Utc>) -> Token {
+        stored_token_on(CHAIN_ID, address, symbol, verified_at)
+    }
+
+    fn stored_token_on(
+        chain_id: ChainId,
+        address: Address,
+        symbol: &str,
+        verified_at: DateTime<Utc>,
+    ) -> Token {
+        let id = super::super::token_id(chain_id, address).unwrap();
+        let mut token = Token::new(
+            id,
+            Some("Token".to_string()),
+            Some(symbol.to_string()),
+            18,
+            MetadataAbi::String,
+        );
+        token.fetched_at = verified_at;
+        token.last_verified_at = verified_at;
+        token
+    }
+
+    fn policy() -> RefreshPolicy {
+        RefreshPolicy {
+            interval: Duration::from_secs(3600),
+            max_age: Duration::from_secs(24 * 3600),
+            batch_size: 100,
+            retry_after: Duration::from_secs(6 * 3600),
+        }
+    }
+
+    #[tokio::test]
+    async fn refresh_records_changed_metadata_and_skips_fresh_tokens() {
+        let rpc = MockServer::start().await;
+        mount_chain_id(&rpc, CHAIN_ID).await;
+        mount_block_number(&rpc, 0x1234).await;
+
+        // Only the stale token is fetched: one aggregate with three calls.
+        let returned = aggregate3_hex(vec![
+            call3_ok("Token".to_string()),
+            call3_ok("NEW".to_string()),
+            call3_ok(U256::from(18)),
+        ]);
+        Mock::given(method("POST"))
+            .and(path("/"))
+            .and(body_is_multicall3_aggregate_eth_call)
+            .respond_with(move |req: &Request| jsonrpc_eth_result_template(req, returned.clone()))
+            .expect(1)
+            .mount(&rpc)
+            .await;
+
+        let repository = InMemoryEvmTokenRepository::default();
+        let long_ago = DateTime::from_timestamp(1_700_000_000, 0).unwrap();
+        let stale = stored_token(Address::repeat_byte(0x01), "OLD", long_ago);
+        let fresh = stored_token(Address::repeat_byte(0x02), "FRESH", Utc::now());
+        repository.save(&stale).await.unwrap();
+        repository.save(&fresh).await.unwrap();
+
+        let service = EvmTokenService::new(Arc::new(repository.clone()), Duration::from_secs(3600));
+        let (_list, providers) = providers(&rpc).await;
+        let report = service.refresh_stale(&providers, &policy()).await.unwrap();
+
+        assert_eq!(
+            report,
+            RefreshReport {
+                verified: 1,
+                changed: 1,
+                failed: 0
+            }
+        );
+        let refreshed: Token = Repository::<Token>::get(&repository, stale.id.clone())
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(refreshed.symbol.as_deref(), Some("NEW"));
+        assert!(refreshed.last_verified_at > long_ago);
+        assert!(refreshed.fetched_at > long_ago);
+        assert_eq!(refreshed.source_block, Some(0x1234));
+    }
+
+    #[tokio::test]
+    async fn refresh_keeps_serving_stored_token_when_chain_fails() {
+        let rpc = MockServer::start().await;
+        mount_chain_id(&rpc, 2).await;
+
+        let repository = InMemoryEvmTokenRepository::default();
+        let long_ago = DateTime::from_timestamp(1_700_000_000, 0).unwrap();
+        let stale = stored_token(Address::repeat_byte(0x01), "OLD", long_ago);
+        repository.save(&stale).await.unwrap();
+
+        let service = EvmTokenService::new(Arc::new(repository.clone()), Duration::from_secs(3600));
+        let (_list, providers) = providers(&rpc).await;
+        let report = service.refresh_stale(&providers, &policy()).await.unwrap();
+
+        assert_eq!(report.failed, 1);
+        let token = service
+            .get_or_fetch_token(CHAIN_ID, Address::repeat_byte(0x01), mock_rpc_client(&rpc))
+            .await
+            .expect("stale token still served");
+        assert_eq!(token.symbol.as_deref(), Some("OLD"));
+        assert_eq!(token.last_verified_at, long_ago);
+    }
+
+    #[tokio::test]
+    async fn failing_tokens_do_not_starve_the_rest_of_the_queue() {
+        let rpc = MockServer::start().await;
+        mount_chain_id(&rpc, CHAIN_ID).await;
+        mount_block_number(&rpc, 0x1234).await;
+        let returned = aggregate3_hex(vec![
+            call3_ok("Token".to_string()),
+            call3_ok("OK".to_string()),
+            call3_ok(U256::from(18)),
+        ]);
+        Mock::given(method("POST"))
+            .and(path("/"))
+            .and(body_is_multicall3_aggregate_eth_call)
+            .respond_with(move |req: &Request| jsonrpc_eth_result_template(req, returned.clone()))
+            .expect(1)
+            .mount(&rpc)
+            .await;
+
+        // More tokens than one batch sit on a chain Chainlist no longer lists, and were verified
+        // before the one that can be refreshed.
+        let repository = InMemoryEvmTokenRepository::default();
+        for byte in 1..=3u8 {
+            let verified_at = DateTime::from_timestamp(1_700_000_000 + i64::from(byte), 0).unwrap();
+            let unlisted = stored_token_on(2, Address::repeat_byte(byte), "GONE", verified_at);
+            repository.save(&unlisted).await.unwrap();
+        }
+        let long_ago = DateTime::from_timestamp(1_700_000_100, 0).unwrap();
+        let refreshable = stored_token(Address::repeat_byte(0x10), "OLD", long_ago);
+        repository.save(&refreshable).await.unwrap();
+
+        let service = EvmTokenService::new(Arc::new(repository.clone()), Duration::from_secs(3600));
+        let (_list, providers) = providers(&rpc).await;
+        let policy = RefreshPolicy {
+            batch_size: 2,
+            ..policy()
+        };
+
+        let first = service.refresh_stale(&providers, &policy).await.unwrap();
+        assert_eq!(first.failed, 2);
+        let second = service.refresh_stale(&providers, &policy).await.unwrap();
+        assert_eq!(
+            second,
+            RefreshReport {
+                verified: 1,
+                changed: 1,
+                failed: 1
+            }
+        );
+        let refreshed: Token = Repository::<Token>::get(&repository, refreshable.id.clone())
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(refreshed.symbol.as_deref(), Some("OK"));
+
+        // Every stale token was attempted within the retry delay, so nothing is left to claim.
+        let third = service.refresh_stale(&providers, &policy).await.unwrap();
+        assert_eq!(third, RefreshReport::default());
+    }
+
+    #[tokio::test]
+    async fn refresh_keeps_the_name_when_name_returns_an_error() {
+        let rpc = MockServer::start().await;
+        mount_chain_id(&rpc, CHAIN_ID).await;
+        mount_block_number(&rpc, 0x1234).await;
+        // No Multicall3, so every field is its own `eth_call`.
+        for matcher in [
+            body_is_multicall3_aggregate_eth_call,
+            body_is_single_eth_get_code,
+        ] {
+            Mock::given(method("POST"))
+                .and(path("/"))
+                .and(matcher)
+                .respond_with(|req: &Request| jsonrpc_eth_result_template(req, "0x".into()))
+                .mount(&rpc)
+                .await;
+        }
+        Mock::given(method("POST"))
+            .and(path("/"))
+            .and(body_is_eth_call_with_input_prefix("06fdde03"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(json!({
+                "jsonrpc": "2.0",
+                "id": 0u64,
+                "error": {"code": -32000, "message": "missing trie node"},
+            })))
+            .mount(&rpc)
+            .await;
+        let symbol = eth_call_hex("OLD".to_string());
+        Mock::given(method("POST"))
+            .and(path("/"))
+            .and(body_is_eth_call_with_input_prefix("95d89b41"))
+            .respond_with(move |req: &Request| jsonrpc_eth_result_template(req, symbol.clone()))
+            .mount(&rpc)
+            .await;
+        Mock::given(method("POST"))
+            .and(path("/"))
+            .and(body_is_eth_call_with_input_prefix("313ce567"))
+            .respond_with(|req: &Request| {
+                jsonrpc_eth_result_template(req, ENCODED_DECIMALS_18.into())
+            })
+            .mount(&rpc)
+            .await;
+
+        let repository = InMemoryEvmTokenRepository::default();
+        let long_ago = DateTime::from_timestamp(1_700_000_000, 0).unwrap();
+        let stale = stored_token(Address::repeat_byte(0x01), "OLD", long_ago);
+        repository.save(&stale).await.unwrap();
+
+        let service = EvmTokenService::new(Arc::new(repository.clone()), Duration::from_secs(3600));
+        let (_list, providers) = providers(&rpc).await;
+        let report = service.refresh_stale(&providers, &policy()).await.unwrap();
+
+        assert_eq!(report.failed, 1);
+        assert_eq!(report.verified, 0);
+        let kept: Token = Repository::<Token>::get(&repository, stale.id.clone())
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(kept.name.as_deref(), Some("Token"));
+        assert_eq!(kept.last_verified_at, long_ago);
+    }
+
+    #[tokio::test]
+    async fn refresh_keeps_a_field_that_comes_back_undecodable() {
+        let rpc = MockServer::start().await;
+        mount_chain_id(&rpc, CHAIN_ID).await;
+        mount_block_number(&rpc, 0x1234).await;
+        // `name()` succeeds with no return data, as some nodes answer calls against pruned state.
+        let returned = aggregate3_hex(vec![
+            IMulticall3::Result {
+                success: true,
+                returnData: Default::default(),
+            },
+            call3_ok("OLD".to_string()),
+            call3_ok(U256::from(18)),
+        ]);
+        Mock::given(method("POST"))
+            .and(path("/"))
+            .and(body_is_multicall3_aggregate_eth_call)
+            .respond_with(move |req: &Request| jsonrpc_eth_result_template(req, returned.clone()))
+            .mount(&rpc)
+            .await;
+
+        let repository = InMemoryEvmTokenRepository::default();
+        let long_ago = DateTime::from_timestamp(1_700_000_000, 0).unwrap();
+        let stale = stored_token(Address::repeat_byte(0x01), "OLD", long_ago);
+        repository.save(&stale).await.unwrap();
+
+        let service = EvmTokenService::new(Arc::new(repository.clone()), Duration::from_secs(3600));
+        let (_list, providers) = providers(&rpc).await;
+        let report = service.refresh_stale(&providers, &policy()).await.unwrap();
+
+        assert_eq!(
+            report,
+            RefreshReport {
+                verified: 0,
+                changed: 0,
+                failed: 1
+            }
+        );
+        let kept: Token = Repository::<Token>::get(&repository, stale.id.clone())
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(kept.name.as_deref(), Some("Token"));
+        assert!(kept.missing_fields.is_empty());
+    }
+}
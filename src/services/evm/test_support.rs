@@ -0,0 +1,144 @@
+//! Wiremock request matchers and response builders shared by the EVM service tests.
+
+use alloy::{
+    primitives::{B256, hex},
+    providers::bindings::IMulticall3::{self, aggregate3Call},
+    rpc::client::RpcClient,
+    sol_types::{SolCall, SolValue},
+    transports::http::Http,
+};
+use serde_json::json;
+use url::Url;
+use wiremock::{
+    Mock, MockServer, Request, ResponseTemplate,
+    matchers::{method, path},
+};
+
+use crate::types::ChainId;
+
+pub(super) fn body_is_single_eth_chain_id(req: &Request) -> bool {
+    let b = String::from_utf8_lossy(&req.body);
+    b.contains("\"eth_chainId\"") && !b.trim_start().starts_with('[')
+}
+
+pub(super) fn body_is_single_eth_get_code(req: &Request) -> bool {
+    let b = String::from_utf8_lossy(&req.body);
+    b.contains("\"eth_getCode\"") && !b.trim_start().starts_with('[')
+}
+
+pub(super) fn body_is_single_eth_get_account(req: &Request) -> bool {
+    let b = String::from_utf8_lossy(&req.body);
+    b.contains("\"eth_getAccount\"") && !b.trim_start().starts_with('[')
+}
+
+pub(super) fn body_is_single_eth_block_number(req: &Request) -> bool {
+    let b = String::from_utf8_lossy(&req.body);
+    b.contains("\"eth_blockNumber\"") && !b.trim_start().starts_with('[')
+}
+
+/// `eth_call` to the canonical Multicall3 address (aggregate), not ERC20 `eth_call`s.
+pub(super) fn body_is_multicall3_aggregate_eth_call(req: &Request) -> bool {
+    let b = String::from_utf8_lossy(&req.body).to_lowercase();
+    b.contains("\"eth_call\"")
+        && !b.trim_start().starts_with('[')
+        && b.contains("ca11bde05977b3631167028862be2a173976ca11")
+}
+
+pub(super) fn body_is_eth_call_with_input_prefix(prefix: &str) -> impl Fn(&Request) -> bool + '_ {
+    let p = prefix.to_lowercase();
+    move |req: &Request| {
+        let b = String::from_utf8_lossy(&req.body).to_lowercase();
+        b.contains("\"eth_call\"") && !b.trim_start().starts_with('[') && b.contains(p.as_str())
+    }
+}
+
+pub(super) fn jsonrpc_eth_result_template(req: &Request, result: String) -> ResponseTemplate {
+    let id = serde_json::from_slice::<serde_json::Value>(&req.body)
+        .ok()
+        .and_then(|v| v.get("id").cloned())
+        .unwrap_or(json!(0));
+    ResponseTemplate::new(200).set_body_json(json!({
+        "jsonrpc": "2.0",
+        "id": id,
+        "result": result,
+    }))
+}
+
+pub(super) fn eth_call_hex(result_word: impl SolValue) -> String {
+    let bytes = result_word.abi_encode();
+    format!("0x{}", hex::encode(bytes))
+}
+
+/// Solidity `uint8` / `decimals()` return: value 18 ABI-encoded as a 32-byte word.
+pub(super) const ENCODED_DECIMALS_18: &str =
+    "0x0000000000000000000000000000000000000000000000000000000000000012";
+
+/// Answers `eth_chainId` with `chain_id`.
+pub(super) async fn mount_chain_id(mock: &MockServer, chain_id: ChainId) {
+    Mock::given(method("POST"))
+        .and(path("/"))
+        .and(body_is_single_eth_chain_id)
+        .respond_with(ResponseTemplate::new(200).set_body_json(json!({
+            "jsonrpc": "2.0",
+            "id": 0u64,
+            "result": format!("0x{:x}", chain_id),
+        })))
+        .mount(mock)
+        .await;
+}
+
+/// Answers `eth_blockNumber` with `block`.
+pub(super) async fn mount_block_number(mock: &MockServer, block: u64) {
+    Mock::given(method("POST"))
+        .and(path("/"))
+        .and(body_is_single_eth_block_number)
+        .respond_with(move |req: &Request| jsonrpc_eth_result_template(req, format!("0x{block:x}")))
+        .mount(mock)
+        .await;
+}
+
+pub(super) fn call3_ok(value: impl SolValue) -> IMulticall3::Result {
+    IMulticall3::Result {
+        success: true,
+        returnData: value.abi_encode().into(),
+    }
+}
+
+pub(super) fn call3_reverted() -> IMulticall3::Result {
+    IMulticall3::Result {
+        success: false,
+        returnData: Default::default(),
+    }
+}
+
+/// `aggregate3` return data for `results`, hex-encoded for an `eth_call` response.
+pub(super) fn aggregate3_hex(results: Vec<IMulticall3::Result>) -> String {
+    format!(
+        "0x{}",
+        hex::encode(aggregate3Call::abi_encode_returns(&results))
+    )
+}
+
+pub(super) fn mock_rpc_client(mock: &MockServer) -> RpcClient {
+    let url: Url = mock.uri().parse().expect("wiremock uri");
+    RpcClient::new(Http::new(url), true)
+}
+
+/// Answers `eth_getAccount` with an account whose `codeHash` is `code_hash`.
+pub(super) async fn mount_account_code_hash(mock: &MockServer, code_hash: B256) {
+    Mock::given(method("POST"))
+        .and(path("/"))
+        .and(body_is_single_eth_get_account)
+        .respond_with(ResponseTemplate::new(200).set_body_json(json!({
+            "jsonrpc": "2.0",
+            "id": 0u64,
+            "result": {
+                "nonce": "0x0",
+                "balance": "0x0",
+                "storageRoot": "0x56e81f171bcc55a6ff8345e692c0f86e5b48e01b996cadc001622fb5e363b421",
+                "codeHash": code_hash,
+            },
+        })))
+        .mount(mock)
+        .await;
+}
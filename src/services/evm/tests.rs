@@ -0,0 +1,693 @@
+//! Offline wiremock tests for [`EvmTokenService`] fetch paths.
+
+use super::test_support::*;
+use super::*;
+use crate::{repositories::memory::evm_token::InMemoryEvmTokenRepository, token::MetadataField};
+use alloy::primitives::{FixedBytes, U256};
+use wiremock::{
+    Mock, MockServer, Request, ResponseTemplate,
+    matchers::{method, path},
+};
+
+const CHAIN_ID: ChainId = 1;
+
+/// Null-padded `bytes32` as returned by MKR-style `name()`/`symbol()`.
+fn bytes32(value: &str) -> FixedBytes<32> {
+    FixedBytes::right_padding_from(value.as_bytes())
+}
+
+#[tokio::test]
+async fn fetch_tokens_uses_one_aggregate3_and_reports_failures_per_token() {
+    let mock = MockServer::start().await;
+    mount_chain_id(&mock, CHAIN_ID).await;
+    mount_account_code_hash(&mock, B256::repeat_byte(0x11)).await;
+
+    let returned = aggregate3_hex(vec![
+        call3_ok("Token A".to_string()),
+        call3_ok("TKA".to_string()),
+        call3_ok(U256::from(6)),
+        call3_ok("Token B".to_string()),
+        call3_ok("TKB".to_string()),
+        call3_reverted(),
+    ]);
+    Mock::given(method("POST"))
+        .and(path("/"))
+        .and(body_is_multicall3_aggregate_eth_call)
+        .respond_with(move |req: &Request| jsonrpc_eth_result_template(req, returned.clone()))
+        .expect(1)
+        .mount(&mock)
+        .await;
+
+    let a = Address::repeat_byte(0x0a);
+    let b = Address::repeat_byte(0x0b);
+    let results = EvmTokenService::fetch_tokens(CHAIN_ID, &[a, b], mock_rpc_client(&mock))
+        .await
+        .expect("batch fetch");
+
+    assert_eq!(results.len(), 2);
+    assert_eq!(results[0].0, a);
+    let token = results[0].1.as_ref().expect("token A");
+    assert_eq!(token.name.as_deref(), Some("Token A"));
+    assert_eq!(token.symbol.as_deref(), Some("TKA"));
+    assert_eq!(token.decimals, 6);
+    assert!(!token.is_partial());
+
+    assert_eq!(results[1].0, b);
+    assert!(matches!(
+        results[1].1,
+        Err(EvmTokenServiceError::InvalidMetadata(_))
+    ));
+}
+
+#[tokio::test]
+async fn fetch_tokens_chain_id_mismatch_fails_whole_batch() {
+    let mock = MockServer::start().await;
+    mount_chain_id(&mock, 10).await;
+
+    Mock::given(method("POST"))
+        .and(path("/"))
+        .and(body_is_multicall3_aggregate_eth_call)
+        .respond_with(ResponseTemplate::new(500))
+        .expect(0)
+        .mount(&mock)
+        .await;
+
+    let err = EvmTokenService::fetch_tokens(
+        CHAIN_ID,
+        &[Address::repeat_byte(0x0a)],
+        mock_rpc_client(&mock),
+    )
+    .await
+    .expect_err("chain id mismatch");
+
+    assert!(matches!(err, EvmTokenServiceError::ChainIdMismatch(10, 1)));
+}
+
+#[tokio::test]
+async fn fetch_token_multicall_decodes_bytes32_name_and_symbol() {
+    let mock = MockServer::start().await;
+    mount_chain_id(&mock, CHAIN_ID).await;
+
+    let returned = aggregate3_hex(vec![
+        call3_ok(bytes32("Maker")),
+        call3_ok(bytes32("MKR")),
+        call3_ok(U256::from(18)),
+    ]);
+    Mock::given(method("POST"))
+        .and(path("/"))
+        .and(body_is_multicall3_aggregate_eth_call)
+        .respond_with(move |req: &Request| jsonrpc_eth_result_template(req, returned.clone()))
+        .mount(&mock)
+        .await;
+
+    let token =
+        EvmTokenService::fetch_token(CHAIN_ID, Address::repeat_byte(0x9f), mock_rpc_client(&mock))
+            .await
+            .expect("bytes32 metadata");
+
+    assert_eq!(token.name.as_deref(), Some("Maker"));
+    assert_eq!(token.symbol.as_deref(), Some("MKR"));
+    assert_eq!(token.decimals, 18);
+    assert_eq!(token.metadata_abi, MetadataAbi::Bytes32);
+}
+
+#[tokio::test]
+async fn fetch_token_rpc_batch_decodes_bytes32_symbol() {
+    let mock = MockServer::start().await;
+    mount_chain_id(&mock, CHAIN_ID).await;
+
+    Mock::given(method("POST"))
+        .and(path("/"))
+        .and(body_is_multicall3_aggregate_eth_call)
+        .respond_with(|req: &Request| jsonrpc_eth_result_template(req, "0x".into()))
+        .mount(&mock)
+        .await;
+
+    Mock::given(method("POST"))
+        .and(path("/"))
+        .and(body_is_single_eth_get_code)
+        .respond_with(|req: &Request| jsonrpc_eth_result_template(req, "0x".into()))
+        .mount(&mock)
+        .await;
+
+    let name_hex = eth_call_hex("Dai Stablecoin v1.0".to_string());
+    let symbol_hex = eth_call_hex(bytes32("SAI"));
+
+    Mock::given(method("POST"))
+        .and(path("/"))
+        .and(body_is_eth_call_with_input_prefix("06fdde03"))
+        .respond_with(move |req: &Request| jsonrpc_eth_result_template(req, name_hex.clone()))
+        .mount(&mock)
+        .await;
+
+    Mock::given(method("POST"))
+        .and(path("/"))
+        .and(body_is_eth_call_with_input_prefix("95d89b41"))
+        .respond_with(move |req: &Request| jsonrpc_eth_result_template(req, symbol_hex.clone()))
+        .mount(&mock)
+        .await;
+
+    Mock::given(method("POST"))
+        .and(path("/"))
+        .and(body_is_eth_call_with_input_prefix("313ce567"))
+        .respond_with(|req: &Request| jsonrpc_eth_result_template(req, ENCODED_DECIMALS_18.into()))
+        .mount(&mock)
+        .await;
+
+    let token =
+        EvmTokenService::fetch_token(CHAIN_ID, Address::repeat_byte(0x89), mock_rpc_client(&mock))
+            .await
+            .expect("rpc batch with bytes32 symbol");
+
+    assert_eq!(token.name.as_deref(), Some("Dai Stablecoin v1.0"));
+    assert_eq!(token.symbol.as_deref(), Some("SAI"));
+    assert_eq!(token.metadata_abi, MetadataAbi::Bytes32);
+}
+
+#[test]
+fn decode_string_or_bytes32_rejects_invalid_utf8() {
+    let mut data = [0u8; 32];
+    data[0] = 0xff;
+    assert!(decode_string_or_bytes32(&data, "name").is_err());
+}
+
+#[tokio::test]
+async fn fetch_token_multicall_reverted_name_yields_partial_token() {
+    let mock = MockServer::start().await;
+    mount_chain_id(&mock, CHAIN_ID).await;
+
+    let returned = aggregate3_hex(vec![
+        call3_reverted(),
+        call3_ok("NONAME".to_string()),
+        call3_ok(U256::from(8)),
+    ]);
+    Mock::given(method("POST"))
+        .and(path("/"))
+        .and(body_is_multicall3_aggregate_eth_call)
+        .respond_with(move |req: &Request| jsonrpc_eth_result_template(req, returned.clone()))
+        .mount(&mock)
+        .await;
+
+    let token =
+        EvmTokenService::fetch_token(CHAIN_ID, Address::repeat_byte(0x33), mock_rpc_client(&mock))
+            .await
+            .expect("partial metadata");
+
+    assert_eq!(token.name, None);
+    assert_eq!(token.symbol.as_deref(), Some("NONAME"));
+    assert_eq!(token.decimals, 8);
+    assert_eq!(token.missing_fields, vec![MetadataField::Name]);
+}
+
+#[tokio::test]
+async fn fetch_token_rpc_batch_reverted_symbol_yields_partial_token() {
+    let mock = MockServer::start().await;
+    mount_chain_id(&mock, CHAIN_ID).await;
+
+    Mock::given(method("POST"))
+        .and(path("/"))
+        .and(body_is_multicall3_aggregate_eth_call)
+        .respond_with(|req: &Request| jsonrpc_eth_result_template(req, "0x".into()))
+        .mount(&mock)
+        .await;
+
+    Mock::given(method("POST"))
+        .and(path("/"))
+        .and(body_is_single_eth_get_code)
+        .respond_with(|req: &Request| jsonrpc_eth_result_template(req, "0x".into()))
+        .mount(&mock)
+        .await;
+
+    let name_hex = eth_call_hex("No Symbol".to_string());
+    Mock::given(method("POST"))
+        .and(path("/"))
+        .and(body_is_eth_call_with_input_prefix("06fdde03"))
+        .respond_with(move |req: &Request| jsonrpc_eth_result_template(req, name_hex.clone()))
+        .mount(&mock)
+        .await;
+
+    Mock::given(method("POST"))
+        .and(path("/"))
+        .and(body_is_eth_call_with_input_prefix("95d89b41"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({
+            "jsonrpc": "2.0",
+            "id": 0u64,
+            "error": {"code": 3, "message": "execution reverted"},
+        })))
+        .mount(&mock)
+        .await;
+
+    Mock::given(method("POST"))
+        .and(path("/"))
+        .and(body_is_eth_call_with_input_prefix("313ce567"))
+        .respond_with(|req: &Request| jsonrpc_eth_result_template(req, ENCODED_DECIMALS_18.into()))
+        .mount(&mock)
+        .await;
+
+    let token =
+        EvmTokenService::fetch_token(CHAIN_ID, Address::repeat_byte(0x44), mock_rpc_client(&mock))
+            .await
+            .expect("partial metadata via eth_call");
+
+    assert_eq!(token.name.as_deref(), Some("No Symbol"));
+    assert_eq!(token.symbol, None);
+    assert_eq!(token.missing_fields, vec![MetadataField::Symbol]);
+}
+
+#[tokio::test]
+async fn fetch_token_rpc_batch_reverted_decimals_is_invalid_metadata() {
+    let mock = MockServer::start().await;
+    mount_chain_id(&mock, CHAIN_ID).await;
+    mount_account_code_hash(&mock, B256::repeat_byte(0x11)).await;
+
+    Mock::given(method("POST"))
+        .and(path("/"))
+        .and(body_is_multicall3_aggregate_eth_call)
+        .respond_with(|req: &Request| jsonrpc_eth_result_template(req, "0x".into()))
+        .mount(&mock)
+        .await;
+
+    Mock::given(method("POST"))
+        .and(path("/"))
+        .and(body_is_single_eth_get_code)
+        .respond_with(|req: &Request| jsonrpc_eth_result_template(req, "0x".into()))
+        .mount(&mock)
+        .await;
+
+    let name_hex = eth_call_hex("No Decimals".to_string());
+    Mock::given(method("POST"))
+        .and(path("/"))
+        .and(body_is_eth_call_with_input_prefix("06fdde03"))
+        .respond_with(move |req: &Request| jsonrpc_eth_result_template(req, name_hex.clone()))
+        .mount(&mock)
+        .await;
+
+    let symbol_hex = eth_call_hex("NODEC".to_string());
+    Mock::given(method("POST"))
+        .and(path("/"))
+        .and(body_is_eth_call_with_input_prefix("95d89b41"))
+        .respond_with(move |req: &Request| jsonrpc_eth_result_template(req, symbol_hex.clone()))
+        .mount(&mock)
+        .await;
+
+    Mock::given(method("POST"))
+        .and(path("/"))
+        .and(body_is_eth_call_with_input_prefix("313ce567"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({
+            "jsonrpc": "2.0",
+            "id": 0u64,
+            "error": {"code": 3, "message": "execution reverted"},
+        })))
+        .mount(&mock)
+        .await;
+
+    let err =
+        EvmTokenService::fetch_token(CHAIN_ID, Address::repeat_byte(0x45), mock_rpc_client(&mock))
+            .await
+            .expect_err("decimals reverted");
+
+    assert!(
+        matches!(err, EvmTokenServiceError::InvalidMetadata(_)),
+        "{err:?}"
+    );
+}
+
+/// Mounts the no-Multicall3 path: `aggregate3` and the canonical `getCode` return nothing, and
+/// `name()`/`symbol()`/`decimals()` answer with a healthy token unless mounted earlier.
+async fn mount_rpc_batch_fallback(mock: &MockServer) {
+    Mock::given(method("POST"))
+        .and(path("/"))
+        .and(body_is_multicall3_aggregate_eth_call)
+        .respond_with(|req: &Request| jsonrpc_eth_result_template(req, "0x".into()))
+        .mount(mock)
+        .await;
+    Mock::given(method("POST"))
+        .and(path("/"))
+        .and(body_is_single_eth_get_code)
+        .respond_with(|req: &Request| jsonrpc_eth_result_template(req, "0x".into()))
+        .mount(mock)
+        .await;
+    for (selector, value) in [
+        ("06fdde03", eth_call_hex("Rate Limited".to_string())),
+        ("95d89b41", eth_call_hex("RATE".to_string())),
+        ("313ce567", ENCODED_DECIMALS_18.to_string()),
+    ] {
+        Mock::given(method("POST"))
+            .and(path("/"))
+            .and(body_is_eth_call_with_input_prefix(selector))
+            .respond_with(move |req: &Request| jsonrpc_eth_result_template(req, value.clone()))
+            .mount(mock)
+            .await;
+    }
+}
+
+/// Answers the first `eth_call` to `selector` with a JSON-RPC error `code`, as rate-limited
+/// providers do.
+async fn mount_error_once(mock: &MockServer, selector: &'static str, code: i64, message: &str) {
+    Mock::given(method("POST"))
+        .and(path("/"))
+        .and(body_is_eth_call_with_input_prefix(selector))
+        .and(|req: &Request| !body_is_multicall3_aggregate_eth_call(req))
+        .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({
+            "jsonrpc": "2.0",
+            "id": 0u64,
+            "error": {"code": code, "message": message},
+        })))
+        .up_to_n_times(1)
+        .mount(mock)
+        .await;
+}
+
+#[tokio::test]
+async fn rate_limited_name_fails_the_lookup_without_clearing_it() {
+    let mock = MockServer::start().await;
+    mount_chain_id(&mock, CHAIN_ID).await;
+    mount_error_once(&mock, "06fdde03", -32005, "daily request count exceeded").await;
+    mount_rpc_batch_fallback(&mock).await;
+
+    let repository = Arc::new(InMemoryEvmTokenRepository::default());
+    let service = EvmTokenService::new(repository.clone(), Duration::from_secs(3600));
+    let address = Address::repeat_byte(0x46);
+    let err = service
+        .get_or_fetch_token(CHAIN_ID, address, mock_rpc_client(&mock))
+        .await
+        .expect_err("rate limited");
+    let EvmTokenServiceError::Shared(err) = err else {
+        panic!("{err:?}")
+    };
+    assert!(matches!(*err, EvmTokenServiceError::Chain(_)), "{err:?}");
+
+    let id = token_id(CHAIN_ID, address).unwrap();
+    let stored: Option<Token> = Repository::<Token>::get(repository.as_ref(), id.clone())
+        .await
+        .unwrap();
+    assert!(stored.is_none());
+
+    let token = service
+        .get_or_fetch_token(CHAIN_ID, address, mock_rpc_client(&mock))
+        .await
+        .expect("retried once the limit clears");
+    assert_eq!(token.name.as_deref(), Some("Rate Limited"));
+    assert!(token.missing_fields.is_empty());
+}
+
+#[tokio::test]
+async fn rate_limited_decimals_is_not_negative_cached() {
+    let mock = MockServer::start().await;
+    mount_chain_id(&mock, CHAIN_ID).await;
+    mount_error_once(&mock, "313ce567", 429, "Too many requests").await;
+    mount_rpc_batch_fallback(&mock).await;
+
+    let repository = Arc::new(InMemoryEvmTokenRepository::default());
+    let service = EvmTokenService::new(repository.clone(), Duration::from_secs(3600));
+    let address = Address::repeat_byte(0x47);
+    let err = service
+        .get_or_fetch_token(CHAIN_ID, address, mock_rpc_client(&mock))
+        .await
+        .expect_err("rate limited");
+    let EvmTokenServiceError::Shared(err) = err else {
+        panic!("{err:?}")
+    };
+    assert!(matches!(*err, EvmTokenServiceError::Chain(_)), "{err:?}");
+    assert!(!err.is_deterministic());
+
+    let negative: Option<NegativeTokenResult> = Repository::<NegativeTokenResult>::get(
+        repository.as_ref(),
+        token_id(CHAIN_ID, address).unwrap(),
+    )
+    .await
+    .unwrap();
+    assert!(negative.is_none());
+
+    let token = service
+        .get_or_fetch_token(CHAIN_ID, address, mock_rpc_client(&mock))
+        .await
+        .expect("retried once the limit clears");
+    assert_eq!(token.decimals, 18);
+}
+
+/// Every call "succeeds" with empty return data, as Multicall3 reports calls to an account without
+/// code.
+fn aggregate3_empty_returns() -> String {
+    let empty = IMulticall3::Result {
+        success: true,
+        returnData: Default::default(),
+    };
+    aggregate3_hex(vec![empty.clone(), empty.clone(), empty])
+}
+
+#[tokio::test]
+async fn fetch_token_without_code_is_not_a_contract() {
+    let mock = MockServer::start().await;
+    mount_chain_id(&mock, CHAIN_ID).await;
+    mount_account_code_hash(&mock, KECCAK256_EMPTY).await;
+
+    let returned = aggregate3_empty_returns();
+    Mock::given(method("POST"))
+        .and(path("/"))
+        .and(body_is_multicall3_aggregate_eth_call)
+        .respond_with(move |req: &Request| jsonrpc_eth_result_template(req, returned.clone()))
+        .mount(&mock)
+        .await;
+
+    let address = Address::repeat_byte(0xee);
+    let err = EvmTokenService::fetch_token(CHAIN_ID, address, mock_rpc_client(&mock))
+        .await
+        .expect_err("EOA");
+
+    assert!(matches!(err, EvmTokenServiceError::NotAContract(a) if a == address));
+}
+
+#[tokio::test]
+async fn fetch_token_contract_with_broken_decimals_stays_decode_error() {
+    let mock = MockServer::start().await;
+    mount_chain_id(&mock, CHAIN_ID).await;
+    mount_account_code_hash(&mock, B256::repeat_byte(0x11)).await;
+
+    let returned = aggregate3_empty_returns();
+    Mock::given(method("POST"))
+        .and(path("/"))
+        .and(body_is_multicall3_aggregate_eth_call)
+        .respond_with(move |req: &Request| jsonrpc_eth_result_template(req, returned.clone()))
+        .mount(&mock)
+        .await;
+
+    let err =
+        EvmTokenService::fetch_token(CHAIN_ID, Address::repeat_byte(0xcc), mock_rpc_client(&mock))
+            .await
+            .expect_err("not an ERC-20");
+
+    assert!(matches!(err, EvmTokenServiceError::InvalidMetadata(_)));
+}
+
+#[tokio::test]
+async fn get_or_fetch_token_serves_not_a_contract_from_negative_cache() {
+    let mock = MockServer::start().await;
+    mount_chain_id(&mock, CHAIN_ID).await;
+    mount_account_code_hash(&mock, KECCAK256_EMPTY).await;
+
+    let returned = aggregate3_empty_returns();
+    Mock::given(method("POST"))
+        .and(path("/"))
+        .and(body_is_multicall3_aggregate_eth_call)
+        .respond_with(move |req: &Request| jsonrpc_eth_result_template(req, returned.clone()))
+        .expect(1)
+        .mount(&mock)
+        .await;
+
+    let service = EvmTokenService::new(
+        Arc::new(InMemoryEvmTokenRepository::default()),
+        Duration::from_secs(3600),
+    );
+    let address = Address::repeat_byte(0xee);
+    for _ in 0..2 {
+        let err = service
+            .get_or_fetch_token(CHAIN_ID, address, mock_rpc_client(&mock))
+            .await
+            .expect_err("EOA");
+        assert!(matches!(err, EvmTokenServiceError::NotAContract(a) if a == address));
+    }
+}
+
+#[tokio::test]
+async fn get_or_fetch_token_does_not_cache_chain_failures() {
+    let mock = MockServer::start().await;
+    Mock::given(method("POST"))
+        .and(path("/"))
+        .and(body_is_single_eth_chain_id)
+        .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({
+            "jsonrpc": "2.0",
+            "id": 0u64,
+            "result": "0x2",
+        })))
+        .expect(2)
+        .mount(&mock)
+        .await;
+
+    let service = EvmTokenService::new(
+        Arc::new(InMemoryEvmTokenRepository::default()),
+        Duration::from_secs(3600),
+    );
+    for _ in 0..2 {
+        let err = service
+            .get_or_fetch_token(CHAIN_ID, Address::repeat_byte(0xee), mock_rpc_client(&mock))
+            .await
+            .expect_err("chain id mismatch");
+        assert!(matches!(err, EvmTokenServiceError::ChainIdMismatch(2, _)));
+    }
+}
+
+#[tokio::test]
+async fn zero_negative_ttl_disables_negative_cache() {
+    let mock = MockServer::start().await;
+    mount_chain_id(&mock, CHAIN_ID).await;
+    mount_account_code_hash(&mock, KECCAK256_EMPTY).await;
+
+    let returned = aggregate3_empty_returns();
+    Mock::given(method("POST"))
+        .and(path("/"))
+        .and(body_is_multicall3_aggregate_eth_call)
+        .respond_with(move |req: &Request| jsonrpc_eth_result_template(req, returned.clone()))
+        .expect(2)
+        .mount(&mock)
+        .await;
+
+    let service = EvmTokenService::new(
+        Arc::new(InMemoryEvmTokenRepository::default()),
+        Duration::ZERO,
+    );
+    for _ in 0..2 {
+        service
+            .get_or_fetch_token(CHAIN_ID, Address::repeat_byte(0xee), mock_rpc_client(&mock))
+            .await
+            .expect_err("EOA");
+    }
+}
+
+#[tokio::test]
+async fn concurrent_lookups_of_one_token_share_a_single_eth_call() {
+    let mock = MockServer::start().await;
+    mount_chain_id(&mock, CHAIN_ID).await;
+
+    let returned = aggregate3_hex(vec![
+        call3_ok("Token A".to_string()),
+        call3_ok("TKA".to_string()),
+        call3_ok(U256::from(6)),
+    ]);
+    Mock::given(method("POST"))
+        .and(path("/"))
+        .and(body_is_multicall3_aggregate_eth_call)
+        .respond_with(move |req: &Request| {
+            jsonrpc_eth_result_template(req, returned.clone()).set_delay(Duration::from_millis(200))
+        })
+        .expect(1)
+        .mount(&mock)
+        .await;
+
+    let service = EvmTokenService::new(
+        Arc::new(InMemoryEvmTokenRepository::default()),
+        Duration::from_secs(3600),
+    );
+    let address = Address::repeat_byte(0xaa);
+    let lookups: Vec<_> = (0..50)
+        .map(|_| {
+            let service = service.clone();
+            let rpc = mock_rpc_client(&mock);
+            tokio::spawn(async move { service.get_or_fetch_token(CHAIN_ID, address, rpc).await })
+        })
+        .collect();
+
+    for lookup in lookups {
+        let token = lookup.await.unwrap().expect("shared token");
+        assert_eq!(token.symbol.as_deref(), Some("TKA"));
+    }
+}
+
+#[tokio::test]
+async fn concurrent_single_and_batch_lookups_share_a_single_eth_call() {
+    let mock = MockServer::start().await;
+    mount_chain_id(&mock, CHAIN_ID).await;
+
+    let returned = aggregate3_hex(vec![
+        call3_ok("Token A".to_string()),
+        call3_ok("TKA".to_string()),
+        call3_ok(U256::from(6)),
+    ]);
+    Mock::given(method("POST"))
+        .and(path("/"))
+        .and(body_is_multicall3_aggregate_eth_call)
+        .respond_with(move |req: &Request| {
+            jsonrpc_eth_result_template(req, returned.clone()).set_delay(Duration::from_millis(200))
+        })
+        .expect(1)
+        .mount(&mock)
+        .await;
+
+    let service = EvmTokenService::new(
+        Arc::new(InMemoryEvmTokenRepository::default()),
+        Duration::from_secs(3600),
+    );
+    let address = Address::repeat_byte(0xaa);
+    let lookups: Vec<_> = (0..20)
+        .map(|i| {
+            let service = service.clone();
+            let rpc = mock_rpc_client(&mock);
+            tokio::spawn(async move {
+                if i % 2 == 0 {
+                    service.get_or_fetch_token(CHAIN_ID, address, rpc).await
+                } else {
+                    let mut results = service
+                        .get_or_fetch_tokens(CHAIN_ID, &[address], rpc)
+                        .await?;
+                    results.pop().expect("one result").1
+                }
+            })
+        })
+        .collect();
+
+    for lookup in lookups {
+        let token = lookup.await.unwrap().expect("shared token");
+        assert_eq!(token.symbol.as_deref(), Some("TKA"));
+    }
+}
+
+#[tokio::test]
+async fn concurrent_lookups_share_a_chain_failure() {
+    let mock = MockServer::start().await;
+    Mock::given(method("POST"))
+        .and(path("/"))
+        .and(body_is_single_eth_chain_id)
+        .respond_with(
+            ResponseTemplate::new(200)
+                .set_body_json(serde_json::json!({
+                    "jsonrpc": "2.0",
+                    "id": 0u64,
+                    "result": "0x2",
+                }))
+                .set_delay(Duration::from_millis(200)),
+        )
+        .expect(1)
+        .mount(&mock)
+        .await;
+
+    let service = EvmTokenService::new(
+        Arc::new(InMemoryEvmTokenRepository::default()),
+        Duration::from_secs(3600),
+    );
+    let lookups: Vec<_> = (0..10)
+        .map(|_| {
+            let service = service.clone();
+            let rpc = mock_rpc_client(&mock);
+            tokio::spawn(async move {
+                service
+                    .get_or_fetch_token(CHAIN_ID, Address::repeat_byte(0xee), rpc)
+                    .await
+            })
+        })
+        .collect();
+
+    for lookup in lookups {
+        let err = lookup.await.unwrap().expect_err("chain id mismatch");
+        assert!(matches!(err, EvmTokenServiceError::ChainIdMismatch(2, _)));
+    }
+}
@@ -1,29 +1,157 @@
 use std::{
-    collections::HashMap,
+    collections::{BTreeMap, HashMap, HashSet},
+    fmt,
     num::NonZeroUsize,
-    sync::Arc,
+    sync::{Arc, Mutex},
+    task::{Context, Poll},
     time::{Duration, Instant},
 };
 
 use alloy::{
-    rpc::client::RpcClient,
-    transports::{http::Http, layers::FallbackLayer, utils::guess_local_url},
+    primitives::U64,
+    rpc::{
+        client::RpcClient,
+        json_rpc::{RequestPacket, ResponsePacket},
+    },
+    transports::{
+        RpcError, TransportError, TransportErrorKind, TransportFut, http::Http,
+        layers::FallbackLayer, utils::guess_local_url,
+    },
 };
+use log::{debug, warn};
+use reqwest::header::{HeaderMap, HeaderName, HeaderValue};
+use serde::Deserialize;
 use thiserror::Error;
-use tokio::sync::RwLock;
-use tower::ServiceBuilder;
+use tokio::{sync::RwLock, task::JoinSet};
+use tower::{Service, ServiceBuilder};
 use url::Url;
 
-use crate::{services::chainlist::ChainlistService, types::ChainId};
+use crate::{
+    chainlist::{Chain, ChainlistError},
+    services::chainlist::ChainlistService,
+    types::ChainId,
+};
 
-/// Parallel transport fan-out for FallbackLayer (Alloy ranks latency + stability).
-const FALLBACK_ACTIVE_CAP: usize = 32;
+/// Default parallel transport fan-out for FallbackLayer (Alloy ranks latency + stability).
+const FALLBACK_ACTIVE_CAP: NonZeroUsize = NonZeroUsize::new(32).unwrap();
+/// How long a Chainlist RPC has to answer `eth_chainId` before it is left out of the client.
+const RPC_PROBE_TIMEOUT: Duration = Duration::from_secs(5);
 
 #[derive(Clone)]
 pub struct ProviderService {
     chainlist: ChainlistService,
     provider_ttl: Duration,
+    fallback_active_cap: NonZeroUsize,
+    rpc_overrides: Arc<HashMap<ChainId, RpcOverride>>,
     cache: Arc<RwLock<HashMap<ChainId, CachedClient>>>,
+    /// Held per chain while its client is built, so concurrent lookups probe a cold chain once
+    /// without holding up other chains.
+    building: Arc<Mutex<HashMap<ChainId, Arc<tokio::sync::Mutex<()>>>>>,
+    /// Chainlist URLs per chain that failed their probe, and when; skipped for the provider TTL.
+    excluded: Arc<Mutex<HashMap<ChainId, HashMap<String, Instant>>>>,
+}
+
+/// A configured RPC endpoint, e.g. a paid provider: its URL and extra HTTP headers such as
+/// `Authorization`. Both may hold API keys, so `Debug` shows only the URL's origin and the header
+/// names.
+#[derive(Clone, PartialEq, Eq, Deserialize)]
+#[serde(from = "RpcEndpointSetting")]
+pub struct RpcEndpoint {
+    pub url: String,
+    pub headers: BTreeMap<String, String>,
+}
+
+/// An endpoint as written in configuration: a bare URL or a table with headers.
+#[derive(Deserialize)]
+#[serde(untagged, deny_unknown_fields)]
+enum RpcEndpointSetting {
+    Url(String),
+    WithHeaders {
+        url: String,
+        #[serde(default)]
+        headers: BTreeMap<String, String>,
+    },
+}
+
+impl From<RpcEndpointSetting> for RpcEndpoint {
+    fn from(setting: RpcEndpointSetting) -> Self {
+        match setting {
+            RpcEndpointSetting::Url(url) => url.into(),
+            RpcEndpointSetting::WithHeaders { url, headers } => Self { url, headers },
+        }
+    }
+}
+
+impl From<String> for RpcEndpoint {
+    fn from(url: String) -> Self {
+        Self {
+            url,
+            headers: BTreeMap::new(),
+        }
+    }
+}
+
+impl RpcEndpoint {
+    /// Scheme, host and port only; paths and query strings often embed API keys.
+    pub fn redacted_url(&self) -> String {
+        match Url::parse(&self.url) {
+            Ok(url) => format!("{}/…", url.origin().ascii_serialization()),
+            Err(_) => "<invalid URL>".to_string(),
+        }
+    }
+
+    /// Headers sent with every request, marked sensitive so `reqwest` never prints their values.
+    pub fn header_map(&self) -> Result<HeaderMap, ProviderServiceError> {
+        let mut headers = HeaderMap::new();
+        for (name, value) in &self.headers {
+            let name = HeaderName::try_from(name.as_str())
+                .map_err(|_| ProviderServiceError::Header(name.clone()))?;
+            let mut value = HeaderValue::try_from(value.as_str())
+                .map_err(|_| ProviderServiceError::Header(name.to_string()))?;
+            value.set_sensitive(true);
+            headers.insert(name, value);
+        }
+        Ok(headers)
+    }
+}
+
+impl fmt::Debug for RpcEndpoint {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("RpcEndpoint")
+            .field("url", &self.redacted_url())
+            .field("headers", &self.headers.keys().collect::<Vec<_>>())
+            .finish()
+    }
+}
+
+/// How configured endpoints for a chain combine with its Chainlist RPCs.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum RpcOverrideMode {
+    /// Configured endpoints first, then Chainlist; they are ranked first until Alloy has latency
+    /// data.
+    #[default]
+    Prepend,
+    /// Only the configured endpoints; Chainlist is not consulted for the chain.
+    Replace,
+    /// Chainlist first, configured endpoints as a last resort.
+    Append,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct RpcOverride {
+    #[serde(default)]
+    pub mode: RpcOverrideMode,
+    pub endpoints: Vec<RpcEndpoint>,
+}
+
+/// A chain [`ProviderService::rpc_client_for_chain`] has RPCs for, with Chainlist's metadata
+/// unless only configured overrides serve it.
+#[derive(Debug, Clone)]
+pub struct SupportedChain {
+    pub chain_id: ChainId,
+    pub metadata: Option<Chain>,
 }
 
 struct CachedClient {
@@ -34,9 +162,14 @@ struct CachedClient {
 #[derive(Debug, Error)]
 pub enum ProviderServiceError {
     #[error(transparent)]
-    Chainlist(#[from] reqwest::Error),
+    Chainlist(#[from] ChainlistError),
     #[error("invalid RPC URL: {0}")]
     Url(#[from] url::ParseError),
+    /// Header name or value that is not valid HTTP; carries the header name only.
+    #[error("invalid RPC header {0}")]
+    Header(String),
+    #[error("failed to build RPC HTTP client: {0}")]
+    HttpClient(#[source] reqwest::Error),
 }
 
 impl ProviderService {
@@ -44,45 +177,120 @@ impl ProviderService {
         Self {
             chainlist,
             provider_ttl,
+            fallback_active_cap: FALLBACK_ACTIVE_CAP,
+            rpc_overrides: Arc::new(HashMap::new()),
             cache: Arc::new(RwLock::new(HashMap::new())),
+            building: Arc::new(Mutex::new(HashMap::new())),
+            excluded: Arc::new(Mutex::new(HashMap::new())),
         }
     }
 
-    /// Cached [`RpcClient`] over Chainlist RPCs using Alloy `FallbackLayer` (keeps transport rankings until TTL).
+    /// Caps how many transports the fallback client queries in parallel.
+    pub fn with_fallback_active_cap(mut self, cap: NonZeroUsize) -> Self {
+        self.fallback_active_cap = cap;
+        self
+    }
+
+    /// Configured endpoints per chain, combined with Chainlist's as each [`RpcOverride::mode`] says.
+    pub fn with_rpc_overrides(mut self, overrides: HashMap<ChainId, RpcOverride>) -> Self {
+        self.rpc_overrides = Arc::new(overrides);
+        self
+    }
+
+    /// Chains with a usable Chainlist RPC or a configured override, by chain id. RPCs are not
+    /// probed here, so a chain whose RPCs all turn out dead or wrong is still listed.
+    pub async fn supported_chains(&self) -> Result<Vec<SupportedChain>, ProviderServiceError> {
+        let chains = self.chainlist.chains_shared().await?;
+        let mut supported: BTreeMap<ChainId, SupportedChain> = chains
+            .iter()
+            .filter(|chain| {
+                self.rpc_overrides.contains_key(&chain.chain_id)
+                    || !self.chainlist.usable_rpc_urls(chain).is_empty()
+            })
+            .map(|chain| {
+                let supported = SupportedChain {
+                    chain_id: chain.chain_id,
+                    metadata: Some(chain.clone()),
+                };
+                (chain.chain_id, supported)
+            })
+            .collect();
+        for &chain_id in self.rpc_overrides.keys() {
+            supported.entry(chain_id).or_insert(SupportedChain {
+                chain_id,
+                metadata: None,
+            });
+        }
+        Ok(supported.into_values().collect())
+    }
+
+    pub async fn supported_chain(
+        &self,
+        chain_id: ChainId,
+    ) -> Result<Option<SupportedChain>, ProviderServiceError> {
+        Ok(self
+            .supported_chains()
+            .await?
+            .into_iter()
+            .find(|chain| chain.chain_id == chain_id))
+    }
+
+    /// Chainlist's entry for `chain_id`, whether or not it has usable RPCs.
+    pub async fn chain_metadata(
+        &self,
+        chain_id: ChainId,
+    ) -> Result<Option<Chain>, ProviderServiceError> {
+        Ok(self.chainlist.get_chain_data(chain_id).await?)
+    }
+
+    /// Cached [`RpcClient`] over Chainlist RPCs and configured overrides using Alloy `FallbackLayer` (keeps transport rankings until TTL).
+    /// Chainlist RPCs are only used once they answer `eth_chainId` with `chain_id`.
     pub async fn rpc_client_for_chain(
         &self,
         chain_id: ChainId,
     ) -> Result<Option<RpcClient>, ProviderServiceError> {
-        {
-            let guard = self.cache.read().await;
-            if let Some(c) = guard.get(&chain_id) {
-                if c.created.elapsed() < self.provider_ttl {
-                    return Ok(Some(c.client.clone()));
-                }
-            }
+        if let Some(client) = self.cached_client(chain_id).await {
+            return Ok(Some(client));
         }
 
-        let mut guard = self.cache.write().await;
-        if let Some(c) = guard.get(&chain_id) {
-            if c.created.elapsed() < self.provider_ttl {
-                return Ok(Some(c.client.clone()));
-            }
+        let build_lock = self
+            .building
+            .lock()
+            .unwrap()
+            .entry(chain_id)
+            .or_default()
+            .clone();
+        let _building = build_lock.lock().await;
+        // Built by another lookup while this one waited.
+        if let Some(client) = self.cached_client(chain_id).await {
+            return Ok(Some(client));
         }
 
-        let Some(urls) = self.chainlist.rpc_urls_for_chain(chain_id).await? else {
-            guard.remove(&chain_id);
-            return Ok(None);
+        let rpc_override = self.rpc_overrides.get(&chain_id);
+        let chainlist_urls = match rpc_override {
+            Some(o) if o.mode == RpcOverrideMode::Replace => None,
+            _ => self.chainlist.rpc_urls_for_chain(chain_id).await?,
+        };
+        let chainlist_urls = match chainlist_urls {
+            Some(urls) => Some(self.verified_urls(chain_id, urls).await?),
+            None => None,
         };
 
-        if urls.is_empty() {
-            guard.remove(&chain_id);
+        let endpoints = endpoints_for_chain(chainlist_urls, rpc_override);
+        if endpoints.is_empty() {
+            self.cache.write().await.remove(&chain_id);
             return Ok(None);
         }
 
-        let client = build_fallback_rpc_client(&urls)?;
+        debug!(
+            "Building RPC client for chain {chain_id} over {} endpoints ({} configured)",
+            endpoints.len(),
+            rpc_override.map_or(0, |o| o.endpoints.len())
+        );
+        let client = build_fallback_rpc_client(&endpoints, self.fallback_active_cap)?;
         let cloned = client.clone();
 
-        guard.insert(
+        self.cache.write().await.insert(
             chain_id,
             CachedClient {
                 created: Instant::now(),
@@ -91,19 +299,219 @@ impl ProviderService {
         );
         Ok(Some(cloned))
     }
+
+    async fn cached_client(&self, chain_id: ChainId) -> Option<RpcClient> {
+        self.cache
+            .read()
+            .await
+            .get(&chain_id)
+            .filter(|c| c.created.elapsed() < self.provider_ttl)
+            .map(|c| c.client.clone())
+    }
+
+    /// `urls` that serve `chain_id`, in their original order. Each URL not already excluded is
+    /// probed with `eth_chainId`; dead or mismatching ones are excluded for the provider TTL. If no
+    /// URL passes, nothing is excluded and all of `urls` are used: the outage is more likely on this
+    /// side than on every RPC at once.
+    async fn verified_urls(
+        &self,
+        chain_id: ChainId,
+        urls: Vec<String>,
+    ) -> Result<Vec<String>, ProviderServiceError> {
+        let excluded = self.excluded_urls(chain_id);
+        let candidates: Vec<String> = urls
+            .iter()
+            .filter(|url| !excluded.contains(*url))
+            .cloned()
+            .collect();
+
+        let mut probes = JoinSet::new();
+        for url in &candidates {
+            let endpoint = RpcEndpoint::from(url.clone());
+            let transport = RpcTransport::new(&endpoint)?;
+            probes.spawn(async move {
+                let result = probe_chain_id(transport, guess_local_url(&endpoint.url)).await;
+                (endpoint, result)
+            });
+        }
+
+        let mut failed = HashSet::new();
+        while let Some(probe) = probes.join_next().await {
+            let Ok((endpoint, result)) = probe else {
+                continue;
+            };
+            match result {
+                Ok(id) if id == chain_id as u64 => {}
+                Ok(id) => {
+                    warn!(
+                        "Excluding RPC {} for chain {chain_id}: it serves chain {id}",
+                        endpoint.redacted_url()
+                    );
+                    failed.insert(endpoint.url);
+                }
+                Err(reason) => {
+                    debug!(
+                        "Excluding RPC {} for chain {chain_id}: {reason}",
+                        endpoint.redacted_url()
+                    );
+                    failed.insert(endpoint.url);
+                }
+            }
+        }
+
+        let verified: Vec<String> = candidates
+            .into_iter()
+            .filter(|url| !failed.contains(url))
+            .collect();
+        if verified.is_empty() && !urls.is_empty() {
+            warn!("No RPC for chain {chain_id} passed its probe; using all of them unprobed");
+            return Ok(urls);
+        }
+        if !failed.is_empty() {
+            self.exclude_urls(chain_id, failed);
+        }
+        Ok(verified)
+    }
+
+    /// URLs of `chain_id` excluded less than the provider TTL ago; older exclusions are dropped.
+    fn excluded_urls(&self, chain_id: ChainId) -> HashSet<String> {
+        let mut excluded = self.excluded.lock().unwrap();
+        let Some(urls) = excluded.get_mut(&chain_id) else {
+            return HashSet::new();
+        };
+        urls.retain(|_, since| since.elapsed() < self.provider_ttl);
+        let live = urls.keys().cloned().collect();
+        if urls.is_empty() {
+            excluded.remove(&chain_id);
+        }
+        live
+    }
+
+    fn exclude_urls(&self, chain_id: ChainId, urls: HashSet<String>) {
+        let now = Instant::now();
+        self.excluded
+            .lock()
+            .unwrap()
+            .entry(chain_id)
+            .or_default()
+            .extend(urls.into_iter().map(|url| (url, now)));
+    }
+}
+
+/// Chain id reported by the RPC behind `transport`. Errors carry no URL.
+async fn probe_chain_id(transport: RpcTransport, is_local: bool) -> Result<u64, String> {
+    let client = RpcClient::new(transport, is_local);
+    match tokio::time::timeout(
+        RPC_PROBE_TIMEOUT,
+        client.request_noparams::<U64>("eth_chainId"),
+    )
+    .await
+    {
+        Ok(Ok(id)) => Ok(id.to()),
+        Ok(Err(e)) => Err(e.to_string()),
+        Err(_) => Err(format!("no answer within {RPC_PROBE_TIMEOUT:?}")),
+    }
 }
 
-fn build_fallback_rpc_client(urls: &[String]) -> Result<RpcClient, ProviderServiceError> {
-    let transports: Vec<Http<reqwest::Client>> = urls
+/// Chainlist URLs combined with a chain's configured endpoints, without duplicate URLs.
+fn endpoints_for_chain(
+    chainlist_urls: Option<Vec<String>>,
+    rpc_override: Option<&RpcOverride>,
+) -> Vec<RpcEndpoint> {
+    let chainlist = chainlist_urls
+        .unwrap_or_default()
+        .into_iter()
+        .map(RpcEndpoint::from);
+    let configured = rpc_override
+        .map(|o| o.endpoints.clone())
+        .unwrap_or_default()
+        .into_iter();
+
+    let ordered: Vec<RpcEndpoint> = match rpc_override.map(|o| o.mode) {
+        Some(RpcOverrideMode::Replace) => configured.collect(),
+        Some(RpcOverrideMode::Prepend) => configured.chain(chainlist).collect(),
+        Some(RpcOverrideMode::Append) | None => chainlist.chain(configured).collect(),
+    };
+
+    let mut seen = HashSet::new();
+    ordered
+        .into_iter()
+        .filter(|endpoint| seen.insert(endpoint.url.clone()))
+        .collect()
+}
+
+/// HTTP transport to one endpoint. Its errors are stripped of the request URL, which may embed an
+/// API key (configured, or expanded from a Chainlist placeholder).
+#[derive(Clone)]
+struct RpcTransport {
+    http: Http<reqwest::Client>,
+}
+
+impl RpcTransport {
+    fn new(endpoint: &RpcEndpoint) -> Result<Self, ProviderServiceError> {
+        let url = Url::parse(&endpoint.url)?;
+        let http = if endpoint.headers.is_empty() {
+            Http::new(url)
+        } else {
+            let client = reqwest::Client::builder()
+                .default_headers(endpoint.header_map()?)
+                .build()
+                .map_err(|e| ProviderServiceError::HttpClient(e.without_url()))?;
+            Http::with_client(client, url)
+        };
+        Ok(Self { http })
+    }
+}
+
+impl fmt::Debug for RpcTransport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("RpcTransport").finish_non_exhaustive()
+    }
+}
+
+impl Service<RequestPacket> for RpcTransport {
+    type Response = ResponsePacket;
+    type Error = TransportError;
+    type Future = TransportFut<'static>;
+
+    fn poll_ready(&mut self, cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        self.http.poll_ready(cx)
+    }
+
+    fn call(&mut self, request: RequestPacket) -> Self::Future {
+        let response = self.http.call(request);
+        Box::pin(async move { response.await.map_err(without_url) })
+    }
+}
+
+fn without_url(error: TransportError) -> TransportError {
+    match error {
+        RpcError::Transport(TransportErrorKind::Custom(custom)) => {
+            match custom.downcast::<reqwest::Error>() {
+                Ok(e) => TransportErrorKind::custom(e.without_url()),
+                Err(custom) => RpcError::Transport(TransportErrorKind::Custom(custom)),
+            }
+        }
+        other => other,
+    }
+}
+
+fn build_fallback_rpc_client(
+    endpoints: &[RpcEndpoint],
+    active_cap: NonZeroUsize,
+) -> Result<RpcClient, ProviderServiceError> {
+    let transports: Vec<RpcTransport> = endpoints
         .iter()
-        .map(|s| Url::parse(s).map(Http::new))
-        .collect::<Result<_, url::ParseError>>()?;
+        .map(RpcTransport::new)
+        .collect::<Result<_, _>>()?;
 
-    let active = NonZeroUsize::new(transports.len().min(FALLBACK_ACTIVE_CAP).max(1))
+    let active = NonZeroUsize::new(transports.len().clamp(1, active_cap.get()))
         .expect("Active transport count must be non-zero");
     let layer = FallbackLayer::default().with_active_transport_count(active);
     let transport = ServiceBuilder::new().layer(layer).service(transports);
-    let is_local = urls.iter().any(|u| guess_local_url(u));
+    let is_local = endpoints
+        .iter()
+        .any(|endpoint| guess_local_url(&endpoint.url));
     Ok(RpcClient::builder().transport(transport, is_local))
 }
 
@@ -115,11 +523,56 @@ mod tests {
     use serde_json::json;
     use wiremock::{
         Mock, MockServer, ResponseTemplate,
-        matchers::{method, path},
+        matchers::{header, method, path},
     };
 
     use super::*;
 
+    fn endpoints(urls: &[&str]) -> Vec<RpcEndpoint> {
+        urls.iter()
+            .map(|url| RpcEndpoint::from(url.to_string()))
+            .collect()
+    }
+
+    fn rpc_override(mode: RpcOverrideMode, urls: &[&str]) -> RpcOverride {
+        RpcOverride {
+            mode,
+            endpoints: endpoints(urls),
+        }
+    }
+
+    /// RPC node answering `eth_chainId` with `chain_id`.
+    async fn rpc_serving(chain_id: ChainId) -> MockServer {
+        let rpc = MockServer::start().await;
+        Mock::given(method("POST"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(json!({
+                "jsonrpc": "2.0",
+                "id": 0,
+                "result": format!("0x{chain_id:x}"),
+            })))
+            .mount(&rpc)
+            .await;
+        rpc
+    }
+
+    /// URL on which nothing listens, so requests to it fail at once.
+    fn dead_url() -> String {
+        let port = std::net::TcpListener::bind("127.0.0.1:0")
+            .unwrap()
+            .local_addr()
+            .unwrap()
+            .port();
+        format!("http://127.0.0.1:{port}/")
+    }
+
+    fn chainlist_at(list: &MockServer) -> ChainlistService {
+        ChainlistService::with_client_and_url(
+            Duration::from_secs(3600),
+            reqwest::Client::new(),
+            format!("{}/rpcs.json", list.uri()),
+        )
+    }
+
     fn chainlist_with_rpc(url: &str, chain_id: ChainId) -> serde_json::Value {
         json!([{
             "name": "Test",
@@ -129,10 +582,154 @@ mod tests {
         }])
     }
 
+    #[test]
+    fn overrides_combine_with_chainlist_by_mode_without_duplicates() {
+        let chainlist = || {
+            Some(vec![
+                "https://a.example".to_string(),
+                "https://b.example".to_string(),
+            ])
+        };
+        let urls = |endpoints: Vec<RpcEndpoint>| -> Vec<String> {
+            endpoints.into_iter().map(|endpoint| endpoint.url).collect()
+        };
+        let configured = ["https://private.example", "https://b.example"];
+
+        assert_eq!(
+            urls(endpoints_for_chain(chainlist(), None)),
+            ["https://a.example", "https://b.example"]
+        );
+        assert_eq!(
+            urls(endpoints_for_chain(
+                chainlist(),
+                Some(&rpc_override(RpcOverrideMode::Prepend, &configured))
+            )),
+            [
+                "https://private.example",
+                "https://b.example",
+                "https://a.example"
+            ]
+        );
+        assert_eq!(
+            urls(endpoints_for_chain(
+                chainlist(),
+                Some(&rpc_override(RpcOverrideMode::Append, &configured))
+            )),
+            [
+                "https://a.example",
+                "https://b.example",
+                "https://private.example"
+            ]
+        );
+        assert_eq!(
+            urls(endpoints_for_chain(
+                None,
+                Some(&rpc_override(RpcOverrideMode::Replace, &configured))
+            )),
+            configured
+        );
+    }
+
+    #[test]
+    fn endpoint_debug_hides_url_path_and_header_values() {
+        let endpoint = RpcEndpoint {
+            url: "https://eth.example:8545/v2/secret-key?token=secret".to_string(),
+            headers: BTreeMap::from([("Authorization".to_string(), "Bearer secret".to_string())]),
+        };
+        let debug = format!("{endpoint:?}");
+        assert!(!debug.contains("secret"), "{debug}");
+        assert!(debug.contains("https://eth.example:8545"), "{debug}");
+        assert!(debug.contains("Authorization"), "{debug}");
+    }
+
+    #[tokio::test]
+    async fn replace_override_sends_headers_and_skips_chainlist() {
+        let list = MockServer::start().await;
+        Mock::given(method("GET"))
+            .respond_with(ResponseTemplate::new(500))
+            .expect(0)
+            .mount(&list)
+            .await;
+
+        let rpc = MockServer::start().await;
+        Mock::given(method("POST"))
+            .and(path("/v2/key"))
+            .and(header("authorization", "Bearer token"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(json!({
+                "jsonrpc": "2.0",
+                "id": 0,
+                "result": "0x1",
+            })))
+            .expect(1)
+            .mount(&rpc)
+            .await;
+
+        let endpoint = RpcEndpoint {
+            url: format!("{}/v2/key", rpc.uri()),
+            headers: BTreeMap::from([("Authorization".to_string(), "Bearer token".to_string())]),
+        };
+        let providers = ProviderService::new(chainlist_at(&list), Duration::from_secs(3600))
+            .with_rpc_overrides(HashMap::from([(
+                1,
+                RpcOverride {
+                    mode: RpcOverrideMode::Replace,
+                    endpoints: vec![endpoint],
+                },
+            )]));
+
+        let client = providers.rpc_client_for_chain(1).await.unwrap().unwrap();
+        let chain_id: alloy::primitives::U64 =
+            client.request_noparams("eth_chainId").await.unwrap();
+        assert_eq!(chain_id.to::<u64>(), 1);
+    }
+
+    #[tokio::test]
+    async fn override_serves_chain_missing_from_chainlist() {
+        let list = MockServer::start().await;
+        let body = chainlist_with_rpc("https://rpc.example/", 42);
+        Mock::given(method("GET"))
+            .and(path("/rpcs.json"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(body))
+            .mount(&list)
+            .await;
+
+        let providers = ProviderService::new(chainlist_at(&list), Duration::from_secs(3600))
+            .with_rpc_overrides(HashMap::from([(
+                7,
+                rpc_override(RpcOverrideMode::Prepend, &["https://private.example"]),
+            )]));
+        assert!(providers.rpc_client_for_chain(7).await.unwrap().is_some());
+    }
+
+    #[tokio::test]
+    async fn transport_errors_omit_the_url() {
+        let url = format!("{}v2/secret-key", dead_url());
+
+        let endpoints = [
+            RpcEndpoint::from(url.clone()),
+            RpcEndpoint {
+                url,
+                headers: BTreeMap::from([(
+                    "Authorization".to_string(),
+                    "Bearer token".to_string(),
+                )]),
+            },
+        ];
+        let client = build_fallback_rpc_client(&endpoints, FALLBACK_ACTIVE_CAP).unwrap();
+        let err = client
+            .request_noparams::<alloy::primitives::U64>("eth_chainId")
+            .await
+            .expect_err("endpoint is gone");
+        assert!(!format!("{err} {err:?}").contains("secret-key"), "{err:?}");
+    }
+
     #[test]
     fn build_fallback_rejects_malformed_url() {
-        let urls = vec!["https://ok.example".to_string(), ":::bad".to_string()];
-        assert!(build_fallback_rpc_client(&urls).is_err());
+        let endpoints = vec![
+            RpcEndpoint::from("https://ok.example".to_string()),
+            RpcEndpoint::from(":::bad".to_string()),
+        ];
+        assert!(build_fallback_rpc_client(&endpoints, FALLBACK_ACTIVE_CAP).is_err());
     }
 
     #[tokio::test]
@@ -181,8 +778,9 @@ mod tests {
 
     #[tokio::test]
     async fn rpc_client_some_when_urls_valid() {
+        let rpc = rpc_serving(1).await;
         let list = MockServer::start().await;
-        let body = chainlist_with_rpc("https://ethereum.publicnode.com", 1);
+        let body = chainlist_with_rpc(&rpc.uri(), 1);
 
         Mock::given(method("GET"))
             .and(path("/rpcs.json"))
@@ -201,8 +799,9 @@ mod tests {
 
     #[tokio::test]
     async fn rpc_client_chainlist_http_fetched_once_while_provider_cache_warm() {
+        let rpc = rpc_serving(42).await;
         let list = MockServer::start().await;
-        let body = chainlist_with_rpc("https://rpc.cache.test/", 42);
+        let body = chainlist_with_rpc(&rpc.uri(), 42);
 
         Mock::given(method("GET"))
             .and(path("/rpcs.json"))
@@ -254,8 +853,9 @@ mod tests {
 
     #[tokio::test]
     async fn rpc_client_rebuilds_after_provider_ttl() {
+        let rpc = rpc_serving(42).await;
         let list = MockServer::start().await;
-        let body = chainlist_with_rpc("https://rpc.ttl.test/", 42);
+        let body = chainlist_with_rpc(&rpc.uri(), 42);
 
         Mock::given(method("GET"))
             .and(path("/rpcs.json"))
@@ -281,4 +881,187 @@ mod tests {
             "expired provider TTL must allocate a new RpcClient"
         );
     }
+
+    #[tokio::test]
+    async fn rpc_client_excludes_chainlist_rpcs_serving_another_chain_or_dead() {
+        let good = rpc_serving(42).await;
+        let wrong = MockServer::start().await;
+        Mock::given(method("POST"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(json!({
+                "jsonrpc": "2.0",
+                "id": 0,
+                "result": "0xa",
+            })))
+            .expect(1)
+            .mount(&wrong)
+            .await;
+
+        let list = MockServer::start().await;
+        let body = json!([{
+            "name": "Test",
+            "chain": "TST",
+            "chainId": 42,
+            "rpc": [{ "url": wrong.uri() }, { "url": dead_url() }, { "url": good.uri() }],
+        }]);
+        Mock::given(method("GET"))
+            .and(path("/rpcs.json"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(body))
+            .mount(&list)
+            .await;
+
+        let providers = ProviderService::new(chainlist_at(&list), Duration::from_secs(3600));
+        let client = providers.rpc_client_for_chain(42).await.unwrap().unwrap();
+        for _ in 0..3 {
+            let chain_id: U64 = client.request_noparams("eth_chainId").await.unwrap();
+            assert_eq!(chain_id.to::<u64>(), 42);
+        }
+    }
+
+    #[tokio::test]
+    async fn rpc_exclusions_expire_per_url_after_the_provider_ttl() {
+        let providers = ProviderService::new(
+            chainlist_at(&MockServer::start().await),
+            Duration::from_millis(200),
+        );
+        providers.exclude_urls(42, HashSet::from(["https://first.test".to_string()]));
+        tokio::time::sleep(Duration::from_millis(120)).await;
+        providers.exclude_urls(42, HashSet::from(["https://second.test".to_string()]));
+        assert_eq!(providers.excluded_urls(42).len(), 2);
+
+        tokio::time::sleep(Duration::from_millis(120)).await;
+        assert_eq!(
+            providers.excluded_urls(42),
+            HashSet::from(["https://second.test".to_string()])
+        );
+    }
+
+    #[tokio::test]
+    async fn rpc_client_keeps_every_rpc_when_none_passes_its_probe() {
+        let list = MockServer::start().await;
+        Mock::given(method("GET"))
+            .and(path("/rpcs.json"))
+            .respond_with(
+                ResponseTemplate::new(200).set_body_json(chainlist_with_rpc(&dead_url(), 42)),
+            )
+            .mount(&list)
+            .await;
+
+        let providers = ProviderService::new(chainlist_at(&list), Duration::from_secs(3600));
+        assert!(providers.rpc_client_for_chain(42).await.unwrap().is_some());
+        assert!(providers.excluded_urls(42).is_empty());
+    }
+
+    #[tokio::test]
+    async fn probing_a_cold_chain_does_not_block_other_chains() {
+        let slow = MockServer::start().await;
+        Mock::given(method("POST"))
+            .respond_with(
+                ResponseTemplate::new(200)
+                    .set_body_json(json!({ "jsonrpc": "2.0", "id": 0, "result": "0x1" }))
+                    .set_delay(Duration::from_secs(2)),
+            )
+            .mount(&slow)
+            .await;
+        let fast = rpc_serving(42).await;
+
+        let list = MockServer::start().await;
+        let body = json!([
+            { "name": "Slow", "chain": "SLW", "chainId": 1, "rpc": [{ "url": slow.uri() }] },
+            { "name": "Fast", "chain": "FST", "chainId": 42, "rpc": [{ "url": fast.uri() }] },
+        ]);
+        Mock::given(method("GET"))
+            .and(path("/rpcs.json"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(body))
+            .mount(&list)
+            .await;
+
+        let providers = ProviderService::new(chainlist_at(&list), Duration::from_secs(3600));
+        let cold = tokio::spawn({
+            let providers = providers.clone();
+            async move { providers.rpc_client_for_chain(1).await }
+        });
+        tokio::time::sleep(Duration::from_millis(200)).await;
+
+        let started = Instant::now();
+        assert!(providers.rpc_client_for_chain(42).await.unwrap().is_some());
+        assert!(started.elapsed() < Duration::from_secs(1));
+        assert!(cold.await.unwrap().unwrap().is_some());
+    }
+
+    #[tokio::test]
+    async fn concurrent_lookups_of_a_cold_chain_probe_once() {
+        let rpc = MockServer::start().await;
+        Mock::given(method("POST"))
+            .respond_with(
+                ResponseTemplate::new(200)
+                    .set_body_json(json!({ "jsonrpc": "2.0", "id": 0, "result": "0x2a" }))
+                    .set_delay(Duration::from_millis(100)),
+            )
+            .expect(1)
+            .mount(&rpc)
+            .await;
+
+        let list = MockServer::start().await;
+        Mock::given(method("GET"))
+            .and(path("/rpcs.json"))
+            .respond_with(
+                ResponseTemplate::new(200).set_body_json(chainlist_with_rpc(&rpc.uri(), 42)),
+            )
+            .mount(&list)
+            .await;
+
+        let providers = ProviderService::new(chainlist_at(&list), Duration::from_secs(3600));
+        let (a, b) = tokio::join!(
+            providers.rpc_client_for_chain(42),
+            providers.rpc_client_for_chain(42)
+        );
+        assert!(a.unwrap().is_some());
+        assert!(b.unwrap().is_some());
+    }
+
+    #[tokio::test]
+    async fn supported_chains_lists_resolvable_chains_and_override_only_ones() {
+        let list = MockServer::start().await;
+        let body = json!([
+            { "name": "Listed", "chain": "LST", "chainId": 42, "rpc": [{ "url": "https://rpc.test" }] },
+            { "name": "WebSocket only", "chain": "WSS", "chainId": 43, "rpc": [{ "url": "wss://rpc.test" }] },
+            { "name": "Overridden", "chain": "OVR", "chainId": 44, "rpc": [] },
+        ]);
+        Mock::given(method("GET"))
+            .and(path("/rpcs.json"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(body))
+            .mount(&list)
+            .await;
+
+        let providers = ProviderService::new(chainlist_at(&list), Duration::from_secs(3600))
+            .with_rpc_overrides(HashMap::from([
+                (
+                    44,
+                    rpc_override(RpcOverrideMode::Replace, &["https://private.example"]),
+                ),
+                (
+                    7,
+                    rpc_override(RpcOverrideMode::Prepend, &["https://private.example"]),
+                ),
+            ]));
+
+        let chains = providers.supported_chains().await.unwrap();
+        let ids: Vec<ChainId> = chains.iter().map(|chain| chain.chain_id).collect();
+        assert_eq!(ids, [7, 42, 44]);
+        assert!(chains[0].metadata.is_none());
+        assert_eq!(chains[2].metadata.as_ref().unwrap().name, "Overridden");
+
+        assert!(providers.supported_chain(43).await.unwrap().is_none());
+        assert_eq!(
+            providers
+                .supported_chain(42)
+                .await
+                .unwrap()
+                .unwrap()
+                .metadata
+                .unwrap()
+                .name,
+            "Listed"
+        );
+    }
 }
@@ -1,12 +1,319 @@
-use serde::Serialize;
-use tap_caip::AccountId;
+use std::{fmt, str::FromStr};
 
-pub type TokenId = AccountId;
+use alloy::primitives::Address;
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Deserializer, Serialize};
+use tap_caip::{AccountId, AssetId};
 
-#[derive(Debug, Clone, Serialize)]
+use crate::{native::NATIVE_TOKEN_ADDRESS, types::ChainId};
+
+/// CAIP-19 asset id of a token: `eip155:1/erc20:0x...` for a contract, `eip155:1/slip44:60` for a
+/// native currency.
+pub type TokenId = AssetId;
+
+/// CAIP-19 asset namespace of ERC-20 contracts.
+pub const ERC20_NAMESPACE: &str = "erc20";
+/// CAIP-19 asset namespace of native currencies, referenced by SLIP-44 coin type.
+pub const SLIP44_NAMESPACE: &str = "slip44";
+
+/// Parses a CAIP-19 token id, or a CAIP-10 account id (`eip155:1:0x...`) as tokens were identified
+/// before, which names an ERC-20 contract.
+pub fn parse_token_id(id: &str) -> Result<TokenId, tap_caip::Error> {
+    if id.contains('/') {
+        return id.parse();
+    }
+    let account = id.parse::<AccountId>()?;
+    TokenId::new(
+        account.chain_id().clone(),
+        ERC20_NAMESPACE,
+        account.address(),
+    )
+}
+
+/// The CAIP-10 form of an ERC-20 token id (`eip155:1:0x...`); `None` for other asset namespaces.
+pub fn legacy_token_id(id: &TokenId) -> Option<String> {
+    (id.namespace() == ERC20_NAMESPACE).then(|| format!("{}:{}", id.chain_id(), id.reference()))
+}
+
+fn deserialize_token_id<'de, D: Deserializer<'de>>(deserializer: D) -> Result<TokenId, D::Error> {
+    let id = String::deserialize(deserializer)?;
+    parse_token_id(&id).map_err(serde::de::Error::custom)
+}
+
+/// What the `address` of a token lookup names.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TokenAddress {
+    Contract(Address),
+    /// The native currency, optionally with the SLIP-44 coin type it was requested by.
+    Native(Option<u32>),
+}
+
+impl TokenAddress {
+    /// Parses `address` as given for `chain_id`: a contract address, the zero or
+    /// [`NATIVE_TOKEN_ADDRESS`] placeholder, a token id on `chain_id` in either form
+    /// [`parse_token_id`] takes, or a bare `slip44:60` asset. `None` if it is none of these.
+    pub fn parse(chain_id: ChainId, address: &str) -> Option<Self> {
+        if let Ok(address) = address.parse::<Address>() {
+            return Some(if address.is_zero() || address == NATIVE_TOKEN_ADDRESS {
+                TokenAddress::Native(None)
+            } else {
+                TokenAddress::Contract(address)
+            });
+        }
+
+        let (namespace, reference) = match parse_token_id(address) {
+            Ok(id) if id.chain_id().to_string() == format!("eip155:{chain_id}") => {
+                (id.namespace().to_owned(), id.reference().to_owned())
+            }
+            Ok(_) => return None,
+            Err(_) => {
+                let (namespace, reference) = address.split_once(':')?;
+                (namespace.to_owned(), reference.to_owned())
+            }
+        };
+        match namespace.as_str() {
+            ERC20_NAMESPACE => Some(TokenAddress::Contract(reference.parse().ok()?)),
+            SLIP44_NAMESPACE => Some(TokenAddress::Native(Some(reference.parse().ok()?))),
+            _ => None,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Token {
+    #[serde(deserialize_with = "deserialize_token_id")]
     pub id: TokenId,
-    pub name: String,
-    pub symbol: String,
+    pub name: Option<String>,
+    pub symbol: Option<String>,
     pub decimals: u8,
+    pub metadata_abi: MetadataAbi,
+    /// Optional ERC-20 fields the contract did not provide (reverted or undecodable).
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub missing_fields: Vec<MetadataField>,
+    /// When the current metadata was first read from chain.
+    pub fetched_at: DateTime<Utc>,
+    /// When the metadata was last confirmed against the chain.
+    pub last_verified_at: DateTime<Utc>,
+    /// Chain head reported by the RPC when the metadata was last read.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub source_block: Option<u64>,
+}
+
+impl Token {
+    /// Builds a token fetched and verified now, deriving [`Token::missing_fields`] from the absent
+    /// optional fields.
+    pub fn new(
+        id: TokenId,
+        name: Option<String>,
+        symbol: Option<String>,
+        decimals: u8,
+        metadata_abi: MetadataAbi,
+    ) -> Self {
+        let mut missing_fields = Vec::new();
+        if name.is_none() {
+            missing_fields.push(MetadataField::Name);
+        }
+        if symbol.is_none() {
+            missing_fields.push(MetadataField::Symbol);
+        }
+
+        let now = Utc::now();
+        Self {
+            id,
+            name,
+            symbol,
+            decimals,
+            metadata_abi,
+            missing_fields,
+            fetched_at: now,
+            last_verified_at: now,
+            source_block: None,
+        }
+    }
+
+    pub fn with_source_block(mut self, source_block: Option<u64>) -> Self {
+        self.source_block = source_block;
+        self
+    }
+
+    pub fn is_partial(&self) -> bool {
+        !self.missing_fields.is_empty()
+    }
+
+    /// Whether `other` carries the same on-chain metadata, ignoring freshness.
+    pub fn same_metadata(&self, other: &Token) -> bool {
+        self.name == other.name
+            && self.symbol == other.symbol
+            && self.decimals == other.decimals
+            && self.metadata_abi == other.metadata_abi
+    }
+}
+
+/// ERC-20 metadata fields that are optional in the standard and may be absent.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum MetadataField {
+    Name,
+    Symbol,
+}
+
+/// ABI shape `name()`/`symbol()` were decoded from: the standard `string`, or a null-padded
+/// `bytes32` used by legacy tokens such as MKR and SAI. `native` marks a chain's native currency,
+/// whose metadata comes from Chainlist rather than a contract.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum MetadataAbi {
+    String,
+    Bytes32,
+    Native,
+}
+
+impl MetadataAbi {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            MetadataAbi::String => "string",
+            MetadataAbi::Bytes32 => "bytes32",
+            MetadataAbi::Native => "native",
+        }
+    }
+}
+
+impl fmt::Display for MetadataAbi {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+impl FromStr for MetadataAbi {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "string" => Ok(MetadataAbi::String),
+            "bytes32" => Ok(MetadataAbi::Bytes32),
+            "native" => Ok(MetadataAbi::Native),
+            other => Err(format!("unknown metadata ABI: {other}")),
+        }
+    }
+}
+
+/// A remembered deterministic lookup failure for [`TokenId`], so repeated lookups of addresses that
+/// are not tokens do not hit the chain again until it expires.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct NegativeTokenResult {
+    #[serde(deserialize_with = "deserialize_token_id")]
+    pub id: TokenId,
+    pub reason: NegativeReason,
+    pub message: String,
+    pub recorded_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum NegativeReason {
+    /// No contract code at the address.
+    NotAContract,
+    /// A contract that does not expose usable ERC-20 metadata.
+    InvalidMetadata,
+}
+
+impl NegativeReason {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            NegativeReason::NotAContract => "not_a_contract",
+            NegativeReason::InvalidMetadata => "invalid_metadata",
+        }
+    }
+}
+
+impl fmt::Display for NegativeReason {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+impl FromStr for NegativeReason {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "not_a_contract" => Ok(NegativeReason::NotAContract),
+            "invalid_metadata" => Ok(NegativeReason::InvalidMetadata),
+            other => Err(format!("unknown negative result reason: {other}")),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use alloy::primitives::address;
+
+    use super::*;
+
+    const DAI: Address = address!("0x6B175474E89094C44Da98b954EedeAC495271d0F");
+
+    #[test]
+    fn token_ids_are_parsed_in_both_forms() {
+        let caip19 = parse_token_id(&format!("eip155:1/erc20:{DAI}")).unwrap();
+        let caip10 = parse_token_id(&format!("eip155:1:{DAI}")).unwrap();
+        assert_eq!(caip19, caip10);
+        assert_eq!(caip10.to_string(), format!("eip155:1/erc20:{DAI}"));
+        assert_eq!(
+            legacy_token_id(&caip19).as_deref(),
+            Some(format!("eip155:1:{DAI}").as_str())
+        );
+
+        let native = parse_token_id("eip155:1/slip44:60").unwrap();
+        assert_eq!(native.namespace(), SLIP44_NAMESPACE);
+        assert_eq!(legacy_token_id(&native), None);
+        assert!(parse_token_id("eip155:1").is_err());
+    }
+
+    #[test]
+    fn legacy_ids_deserialize() {
+        let json = format!(r#""eip155:10:{DAI}""#);
+        let id = deserialize_token_id(&mut serde_json::Deserializer::from_str(&json)).unwrap();
+        assert_eq!(id.to_string(), format!("eip155:10/erc20:{DAI}"));
+    }
+
+    #[test]
+    fn lookup_addresses_name_contracts_or_the_native_currency() {
+        let parse = TokenAddress::parse;
+        assert_eq!(
+            parse(1, "0x0000000000000000000000000000000000000000"),
+            Some(TokenAddress::Native(None))
+        );
+        assert_eq!(
+            parse(1, "0xeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeee"),
+            Some(TokenAddress::Native(None))
+        );
+        assert_eq!(parse(1, "slip44:60"), Some(TokenAddress::Native(Some(60))));
+        assert_eq!(
+            parse(137, "eip155:137/slip44:966"),
+            Some(TokenAddress::Native(Some(966)))
+        );
+        assert_eq!(
+            parse(1, &DAI.to_string()),
+            Some(TokenAddress::Contract(DAI))
+        );
+        assert_eq!(
+            parse(1, &format!("eip155:1/erc20:{DAI}")),
+            Some(TokenAddress::Contract(DAI))
+        );
+        assert_eq!(
+            parse(1, &format!("eip155:1:{DAI}")),
+            Some(TokenAddress::Contract(DAI))
+        );
+    }
+
+    #[test]
+    fn other_assets_and_chains_are_rejected() {
+        let parse = TokenAddress::parse;
+        assert_eq!(parse(1, "eip155:137/slip44:966"), None);
+        assert_eq!(parse(1, &format!("eip155:10:{DAI}")), None);
+        assert_eq!(parse(1, &format!("eip155:1/erc721:{DAI}")), None);
+        assert_eq!(parse(1, "erc20:0x6B17"), None);
+        assert_eq!(parse(1, "slip44:eth"), None);
+        assert_eq!(parse(1, "not-an-address"), None);
+    }
 }